@@ -1,17 +1,37 @@
 // (c) 2023 Pttn (Stelo.xyz/Riecoin.dev) and contributors
 
+// Lints conflicting with the code style of Stella, the other ones are fixed
+#![allow(clippy::needless_return)] // Functions end with an explicit return
+#![allow(clippy::redundant_field_names)] // Structs are initialized with field: value even when the names are the same
+#![allow(clippy::unused_unit)] // Functions returning nothing may be written with -> ()
+
+pub mod protocol;
+
 use rug::Integer;
-use std::collections::HashMap;
-use std::collections::VecDeque;
+use rug::integer::Order;
+use rug::ops::RemRounding;
+use rug::rand::RandState;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
 use std::mem::size_of;
-use std::sync::{Arc, Mutex, Condvar};
+use std::sync::{Arc, Mutex, MutexGuard, Condvar};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::thread;
 use std::thread::available_parallelism;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-pub const WORD_SIZE: usize = 8*size_of::<usize>();
+pub const WORD_SIZE: usize = usize::BITS as usize;
+// The sieve and trial divisions multiply remainders modulo the primes in native integers, so the primes must be lower than the square root of usize::MAX (2^32 on 64 bits machines, 2^16 on 32 bits ones)
+pub const MAX_PRIME_TABLE_LIMIT: usize = 1 << (usize::BITS/2);
 
-pub const DEFAULT_PRIMORIAL_OFFSETS: &'static [(&'static [isize], u128)] = &[
+pub const DEFAULT_PRIMORIAL_OFFSETS: &[(&[isize], u128)] = &[
 	(&[0], 380284918609481),
 	(&[0, 2], 380284918609481),
 	(&[0, 2, 6], 380284918609481),
@@ -37,6 +57,150 @@ pub const DEFAULT_PRIMORIAL_OFFSETS: &'static [(&'static [isize], u128)] = &[
 	(&[0, 6, 10, 12, 16, 22, 24, 30, 34, 36, 40, 42], 1418575498567)
 ];
 
+// Get the hardcoded Primorial Offset associated to a Constellation Pattern, if any
+// The pattern is normalized (sorted and shifted to start at 0) before the lookup, and the offset adjusted accordingly, so equivalent patterns like 2, 4, 8 and 0, 2, 6 are handled the same way
+pub fn default_offset_for(pattern: &[isize]) -> Option<u128> {
+	let mut normalized_pattern = pattern.to_vec();
+	normalized_pattern.sort();
+	let shift = *normalized_pattern.first()?;
+	for offset in normalized_pattern.iter_mut() {
+		*offset -= shift;
+	}
+	let default_offset = DEFAULT_PRIMORIAL_OFFSETS.iter().find(|&&x| x.0 == &normalized_pattern[..])?.1;
+	// If n + offset is prime for every offset of the normalized pattern, then (n - shift) + offset is for every offset of the original one
+	return u128::try_from(default_offset as i128 - shift as i128).ok();
+}
+
+// Well-known names of the prime k-tuplets, associated to the first (densest) pattern of this length in DEFAULT_PRIMORIAL_OFFSETS
+pub const PATTERN_NAMES: &[(&str, &[isize])] = &[
+	("single", &[0]),
+	("twin", &[0, 2]),
+	("triplet", &[0, 2, 6]),
+	("quadruplet", &[0, 2, 6, 8]),
+	("quintuplet", &[0, 2, 6, 8, 12]),
+	("sextuplet", &[0, 4, 6, 10, 12, 16]),
+	("septuplet", &[0, 2, 6, 8, 12, 18, 20]),
+	("octuplet", &[0, 2, 6, 8, 12, 18, 20, 26]),
+	("nonuplet", &[0, 2, 6, 8, 12, 18, 20, 26, 30]),
+	("decuplet", &[0, 2, 6, 8, 12, 18, 20, 26, 30, 32]),
+	("undecuplet", &[0, 2, 6, 8, 12, 18, 20, 26, 30, 32, 36]),
+	("duodecuplet", &[0, 2, 6, 8, 12, 18, 20, 26, 30, 32, 36, 42])
+];
+
+// Get the pattern of a prime k-tuplet from its name, like "sextuplet" or "Prime Septuplet" (case insensitive, the "prime" prefix is optional)
+pub fn pattern_by_name(name: &str) -> Option<Vec<isize>> {
+	let name = name.trim().to_lowercase();
+	let name = name.strip_prefix("prime ").unwrap_or(&name).trim();
+	return PATTERN_NAMES.iter().find(|&&x| x.0 == name).map(|x| x.1.to_vec());
+}
+
+// Whether a pattern is admissible, that is whether it can contain only primes infinitely often (by the Hardy-Littlewood k-tuple conjecture)
+// This is the case if for every prime p, the offsets do not cover all the residues modulo p, which only needs to be checked for p not bigger than the pattern length
+pub fn is_admissible(pattern: &[isize]) -> bool {
+	return inadmissible_prime(pattern).is_none();
+}
+
+// Smallest prime p for which the offsets cover all the residues modulo p, making one of the numbers always divisible by p
+fn inadmissible_prime(pattern: &[isize]) -> Option<usize> {
+	for p in generate_primes(pattern.len()) {
+		let mut residues = vec![false ; p];
+		for offset in pattern {
+			residues[offset.rem_euclid(p as isize) as usize] = true;
+		}
+		if residues.iter().all(|&covered| covered) {
+			return Some(p);
+		}
+	}
+	return None;
+}
+
+// Hardy-Littlewood constant of the pattern (singular series), the product over the given primes of (1 - ν_p/p)/(1 - 1/p)^k, where ν_p is the number of distinct residues of the offsets modulo p and k the number of distinct offsets (a repeated offset is the same number)
+// By the k-tuple conjecture, the number of tuples below x is asymptotically this constant times the integral of 1/ln(t)^k. The product converges, so the primes of a table give a close approximation (0 for an inadmissible pattern)
+pub fn hardy_littlewood_constant(pattern: &[isize], primes: &[usize]) -> f64 {
+	let mut offsets = pattern.to_vec();
+	offsets.sort();
+	offsets.dedup();
+	let width = match (offsets.first(), offsets.last()) {
+		(Some(&first), Some(&last)) => last.abs_diff(first),
+		_ => {return 1f64;}
+	};
+	let k = offsets.len() as i32;
+	let mut constant = 1f64;
+	for &p in primes {
+		let residue_count = match p > width { // Then the offsets are distinct modulo p
+			true => offsets.len(),
+			false => {
+				let mut residues: Vec<usize> = offsets.iter().map(|offset| offset.rem_euclid(p as isize) as usize).collect();
+				residues.sort();
+				residues.dedup();
+				residues.len()
+			}
+		};
+		constant *= (1f64 - (residue_count as f64)/(p as f64))/(1f64 - 1f64/(p as f64)).powi(k);
+	}
+	return constant;
+}
+
+// Pattern given by the gaps between its consecutive numbers, like 2, 4, 2, 4, 6, 2 for 0, 2, 6, 8, 12, 18, 20
+// The gaps must be positive and the resulting pattern admissible
+pub fn pattern_from_gaps(gaps: &[isize]) -> Result<Vec<isize>, String> {
+	let mut pattern: Vec<isize> = vec![0];
+	for &gap in gaps {
+		if gap <= 0 {
+			return Err(format!("The gap {} is not positive.", gap));
+		}
+		match pattern.last().unwrap().checked_add(gap) {
+			Some(offset) => {pattern.push(offset);}
+			None => {return Err("The gaps are too big to be expressed as a pattern.".to_string());}
+		}
+	}
+	if let Some(p) = inadmissible_prime(&pattern) {
+		return Err(format!("The pattern {:?} is not admissible, one of its numbers is always divisible by {}.", pattern, p));
+	}
+	return Ok(pattern);
+}
+
+// Gaps between the consecutive numbers of a pattern, the inverse of pattern_from_gaps for a pattern starting with 0
+pub fn gaps_from_pattern(pattern: &[isize]) -> Vec<isize> {
+	return pattern.windows(2).map(|pair| pair[1] - pair[0]).collect();
+}
+
+// Search of k primes in arithmetic progression a, a + d, a + 2d,... (AP-k)
+// For now, only a fixed common difference d is supported, the progression can then be searched like a Constellation Pattern 0, d, 2d,...
+#[derive(Clone, Debug)]
+pub struct ArithmeticProgression {
+	pub k: usize,
+	pub common_difference: Option<Integer> // None for a variable common difference
+}
+
+impl ArithmeticProgression {
+	// Pattern corresponding to the progression, to be used as constellation_pattern and Job pattern
+	// Such patterns do not have a default Primorial Offset, so it must be set manually
+	pub fn pattern(&self) -> Result<Vec<isize>, String> {
+		if self.k == 0 {
+			return Err("The progression must contain at least one number.".to_string());
+		}
+		let common_difference = match &self.common_difference {
+			Some(common_difference) => common_difference,
+			None => {return Err("Arithmetic Progressions with a variable common difference are not supported yet.".to_string());}
+		};
+		if *common_difference <= 0 {
+			return Err("The common difference must be positive.".to_string());
+		}
+		let mut pattern = vec![];
+		for i in 0 .. self.k {
+			match (common_difference.clone()*i).to_isize() {
+				Some(offset) => {pattern.push(offset);}
+				None => {return Err("The common difference is too big to be expressed as a pattern.".to_string());}
+			}
+		}
+		if let Some(p) = inadmissible_prime(&pattern) {
+			return Err(format!("The progression is not admissible, one of its numbers is always divisible by {}. The common difference must be a multiple of every prime up to k.", p));
+		}
+		return Ok(pattern);
+	}
+}
+
 // Struct containing the relevant information for a job submitted to the Stella instance
 #[derive(Clone)]
 pub struct Job {
@@ -47,10 +211,146 @@ pub struct Job {
 	pub target_max: Integer,
 	pub k_min: usize,
 	pub pattern_min: Vec<bool>,
+	pub dedup_outputs: bool, // Remember the primorial factors of the outputs to never report twice the same tuple
+	pub alignment: Option<Integer>, // If set, only search numbers n such that n - target_min is a multiple of it
+	pub skip_primality_test: bool, // Output every Candidate surviving the sieve without testing it, for example to feed another tool
+	pub partial_output_threshold: usize, // If not 0, tuples with at least this number of primes but less than k_min are pushed to a separate queue
+	pub min_output_spacing: Integer, // If not 0, an output is only emitted if its n differs from the one of the last emitted output of the Job by at least this
+	pub factor_stride: Option<(usize, usize)>, // If set to (stride, offset), only the Candidates whose primorial factor f satisfies f % stride == offset are checked, to split a range between independent instances
+}
+
+impl Job {
+	// Builder for Jobs, k_min defaults to the pattern length and pattern_min to all true. The target range must be set
+	pub fn builder(id: usize, pattern: Vec<isize>) -> JobBuilder {
+		return JobBuilder {
+			job: Job {
+				id: id,
+				clear_previous_jobs: false,
+				k_min: pattern.len(),
+				pattern_min: vec![true; pattern.len()],
+				pattern: pattern,
+				target_min: Integer::from(0),
+				target_max: Integer::from(0),
+				dedup_outputs: false,
+				alignment: None,
+				skip_primality_test: false,
+				partial_output_threshold: 0,
+				min_output_spacing: Integer::from(0),
+				factor_stride: None
+			},
+			target_min: None,
+			target_max: None
+		};
+	}
+	
+	// Checks the consistency of the fields that do not depend on the Stella instance, returns the errors
+	pub fn validate(&self) -> Vec<String> {
+		let mut errors = vec![];
+		if self.pattern.len() != self.pattern_min.len() {
+			errors.push(format!("The target pattern {:?} and minimum pattern {:?} Vecs must have the same size.", self.pattern, self.pattern_min).to_string());
+		}
+		if self.k_min > self.pattern.len() {
+			errors.push(format!("The minimum tuple length {} must not exceed the constellation pattern length {}.", self.k_min, self.pattern.len()).to_string());
+		}
+		if self.target_max < self.target_min {
+			errors.push("The target upper bound must be higher than the target lower bound.".to_string());
+		}
+		if let Some((stride, offset)) = self.factor_stride {
+			if offset >= stride {
+				errors.push(format!("The factor stride offset {} must be lower than the stride {}.", offset, stride).to_string());
+			}
+		}
+		return errors;
+	}
+}
+
+pub struct JobBuilder {
+	job: Job,
+	target_min: Option<Integer>,
+	target_max: Option<Integer>
+}
+
+impl JobBuilder {
+	pub fn target_min(mut self, target_min: Integer) -> JobBuilder {
+		self.target_min = Some(target_min);
+		return self;
+	}
+	
+	pub fn target_max(mut self, target_max: Integer) -> JobBuilder {
+		self.target_max = Some(target_max);
+		return self;
+	}
+	
+	pub fn k_min(mut self, k_min: usize) -> JobBuilder {
+		self.job.k_min = k_min;
+		return self;
+	}
+	
+	pub fn pattern_min(mut self, pattern_min: Vec<bool>) -> JobBuilder {
+		self.job.pattern_min = pattern_min;
+		return self;
+	}
+	
+	pub fn clear_previous_jobs(mut self, clear_previous_jobs: bool) -> JobBuilder {
+		self.job.clear_previous_jobs = clear_previous_jobs;
+		return self;
+	}
+	
+	pub fn dedup_outputs(mut self, dedup_outputs: bool) -> JobBuilder {
+		self.job.dedup_outputs = dedup_outputs;
+		return self;
+	}
+	
+	pub fn alignment(mut self, alignment: Option<Integer>) -> JobBuilder {
+		self.job.alignment = alignment;
+		return self;
+	}
+	
+	pub fn skip_primality_test(mut self, skip_primality_test: bool) -> JobBuilder {
+		self.job.skip_primality_test = skip_primality_test;
+		return self;
+	}
+	
+	pub fn partial_output_threshold(mut self, partial_output_threshold: usize) -> JobBuilder {
+		self.job.partial_output_threshold = partial_output_threshold;
+		return self;
+	}
+	
+	pub fn min_output_spacing(mut self, min_output_spacing: Integer) -> JobBuilder {
+		self.job.min_output_spacing = min_output_spacing;
+		return self;
+	}
+	
+	pub fn factor_stride(mut self, factor_stride: Option<(usize, usize)>) -> JobBuilder {
+		self.job.factor_stride = factor_stride;
+		return self;
+	}
+	
+	// Returns the Job, or the errors if the target range was not set or if the Job is not valid
+	pub fn build(self) -> Result<Job, Vec<String>> {
+		let mut job = self.job;
+		match (self.target_min, self.target_max) {
+			(Some(target_min), Some(target_max)) => {
+				job.target_min = target_min;
+				job.target_max = target_max;
+			}
+			_ => {return Err(vec!["Both target_min and target_max must be set.".to_string()]);}
+		}
+		let errors = job.validate();
+		if !errors.is_empty() {
+			return Err(errors);
+		}
+		return Ok(job);
+	}
 }
 
 #[derive(PartialEq)] enum TaskType {Sieve, Check}
 const MAX_CANDIDATES_PER_CHECK_TASK: usize = 64;
+const ABC_HEADER: &str = "ABC $a+$b"; // ABC format of the outputs, $a being the base number and $b the offset
+const CROSS_CHECK_REPS: u32 = 30; // Repetitions of GMP's is_probably_prime for cross_check
+const MIN_BUDGET_PRIME_TABLE_LIMIT: usize = 65536; // The memory_budget does not reduce the prime table below this
+const DEFAULT_MAX_HELD_OUTPUTS: usize = 65536;
+pub const MAX_QUEUED_EVENTS: usize = 1024; // Beyond this, the oldest Events are dropped, so a queue that is not popped does not grow indefinitely
 // Struct containing the relevant information for internal tasks created to do the Jobs
 struct Task {
 	pub t: TaskType,
@@ -83,12 +383,173 @@ impl Task {
 }
 
 // Struct for results of interest found by a Stella instance (actual prime k-tuplet, long enough tuple, or pool share).
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Output {
 	pub n: Integer,
 	pub pattern: Vec<isize>,
 	pub job_id: usize,
-	pub worker_id: usize
+	pub worker_id: usize,
+	pub verified: bool, // False for the untested Candidates of Jobs using skip_primality_test, the pattern is then the whole Job pattern
+	pub seq: u64 // Discovery sequence number, shared by both output queues and increasing during the whole life of the instance
+}
+
+impl Output {
+	// Base number of the tuple written in the given radix (from 2 to 36), for example 16 for a more compact output than decimal
+	pub fn format(&self, radix: i32) -> String {
+		return self.n.to_string_radix(radix);
+	}
+	
+	// The tuple in the ABC format read by PFGW and similar tools, a line "ABC $a+$b" followed by a line "n offset" per number of the tuple, so each one is tested as n + offset
+	pub fn to_abc_format(&self) -> String {
+		return format!("{}\n{}", ABC_HEADER, self.abc_lines());
+	}
+	
+	// Writes the tuples of the outputs to a single ABC file (for example a std::fs::File), with one header for all of them
+	pub fn write_abc<W: Write>(outputs: &[Output], writer: &mut W) -> std::io::Result<()> {
+		writeln!(writer, "{}", ABC_HEADER)?;
+		for output in outputs {
+			write!(writer, "{}", output.abc_lines())?;
+		}
+		return writer.flush();
+	}
+	
+	fn abc_lines(&self) -> String {
+		return self.pattern.iter().map(|offset| format!("{} {}\n", self.n, offset)).collect();
+	}
+}
+
+// Outputs are compared by base number, then pattern and Job Id. The worker Id and sequence number are ignored, as the same tuple found by different workers is the same result
+// The hash uses the same fields, so equal Outputs have the same hash and can be deduplicated with a HashSet
+impl PartialEq for Output {
+	fn eq(&self, other: &Output) -> bool {
+		return self.cmp(other) == std::cmp::Ordering::Equal;
+	}
+}
+
+impl Eq for Output {}
+
+impl Hash for Output {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.n.hash(state);
+		self.pattern.hash(state);
+		self.job_id.hash(state);
+	}
+}
+
+impl PartialOrd for Output {
+	fn partial_cmp(&self, other: &Output) -> Option<std::cmp::Ordering> {
+		return Some(self.cmp(other));
+	}
+}
+
+impl Ord for Output {
+	fn cmp(&self, other: &Output) -> std::cmp::Ordering {
+		return self.n.cmp(&other.n).then_with(|| self.pattern.cmp(&other.pattern)).then_with(|| self.job_id.cmp(&other.job_id));
+	}
+}
+
+// In which order the numbers of a Candidate tuple are tested. Testing first the positions that are the most likely to be composite makes the checks stop earlier on average.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CheckOrder {
+	LeftToRight, // Positions 0, 1, 2,...
+	Centered, // Middle position first, then alternately the next ones on the right and on the left
+	Probabilities(Vec<f64>) // Prime probability of each position, for example from calibrate, the least likely positions first (the ones without a probability last)
+}
+
+impl CheckOrder {
+	// Positions of a pattern of the given length in the order in which they must be tested
+	fn positions(&self, length: usize) -> Vec<usize> {
+		match self {
+			CheckOrder::LeftToRight => {return (0 .. length).collect();}
+			CheckOrder::Centered => {
+				if length == 0 {return vec![];}
+				let center = (length - 1)/2;
+				let mut positions = vec![center];
+				for d in 1 .. length {
+					if center + d < length {positions.push(center + d);}
+					if d <= center {positions.push(center - d);}
+				}
+				return positions;
+			}
+			CheckOrder::Probabilities(probabilities) => {
+				let mut positions: Vec<usize> = (0 .. length).collect();
+				positions.sort_by(|&a, &b| {
+					let (probability_a, probability_b) = (probabilities.get(a).copied().unwrap_or(1.), probabilities.get(b).copied().unwrap_or(1.));
+					return probability_a.partial_cmp(&probability_b).unwrap_or(std::cmp::Ordering::Equal);
+				});
+				return positions;
+			}
+		}
+	}
+}
+
+// Which Tasks a worker processes, set by sieve_workers and check_workers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WorkerRole {
+	Any,
+	Sieve,
+	Check
+}
+
+impl WorkerRole {
+	// The sieve_workers first workers only sieve, the check_workers next ones only check, and the others do both
+	fn of(worker_id: usize, params: &Params) -> WorkerRole {
+		if worker_id < params.sieve_workers {
+			return WorkerRole::Sieve;
+		}
+		if worker_id < params.sieve_workers + params.check_workers {
+			return WorkerRole::Check;
+		}
+		return WorkerRole::Any;
+	}
+	
+	fn accepts(&self, task_type: &TaskType) -> bool {
+		return match self {
+			WorkerRole::Any => true,
+			WorkerRole::Sieve => *task_type == TaskType::Sieve,
+			WorkerRole::Check => *task_type == TaskType::Check
+		};
+	}
+}
+
+// Probable prime test used for the tier 2 of the checks, after the trial divisions
+#[derive(Clone, Debug, PartialEq)]
+pub enum PrimalityTest {
+	Fermat, // 2^(n - 1) ≡ 1 (mod n)
+	Euler // 2^((n - 1)/2) ≡ (2/n) (mod n), with (2/n) the Jacobi symbol. Similar cost, but rejects some composites passing the Fermat test (like 341 = 11 × 31)
+}
+
+impl PrimalityTest {
+	// Whether n is a probable prime according to this test. Numbers below 4 are handled separately, and other even numbers are rejected
+	pub fn is_probable_prime(&self, n: &Integer) -> bool {
+		return match self {
+			PrimalityTest::Fermat => is_prime_fermat(n),
+			PrimalityTest::Euler => is_prime_euler(n)
+		};
+	}
+	
+	fn is_probable_prime_u64(&self, n: u64) -> bool {
+		return match self {
+			PrimalityTest::Fermat => is_prime_fermat_u64(n),
+			PrimalityTest::Euler => is_prime_euler_u64(n)
+		};
+	}
+}
+
+// Where the Check Tasks made by a Sieve Task are inserted in the Task queue
+#[derive(Clone, Debug, PartialEq)]
+pub enum CheckTaskInsertion {
+	Front, // Processed before the queued Tasks, the freshest Candidates first
+	Back // Processed after the queued Tasks, including the continuation of the sieve
+}
+
+impl CheckTaskInsertion {
+	fn insert(&self, tasks: &mut VecDeque<Task>, task: Task) {
+		match self {
+			CheckTaskInsertion::Front => tasks.push_front(task),
+			CheckTaskInsertion::Back => tasks.push_back(task)
+		}
+	}
 }
 
 // Struct containing parameters for a Stella instance.
@@ -97,9 +558,30 @@ pub struct Params {
 	pub workers: usize,
 	pub constellation_pattern: Vec<isize>,
 	pub prime_table_limit: usize,
+	pub prime_count: Option<usize>, // Alternatively, generate exactly this number of primes
 	pub primorial_number: usize,
 	pub primorial_offset: u128,
 	pub sieve_size: usize,
+	pub single_sieve_window: bool, // For testing, cut the range of each Job after its first sieve window
+	pub check_order: CheckOrder,
+	pub sieve_buffer_pool_size: usize, // If lower than workers, Sieve Tasks borrow their buffers from a shared pool of this size instead of having one per worker
+	pub verify_sieve: bool, // For debugging, check that a sample of the Candidates are indeed not divisible by the sieving primes
+	pub verify_sieve_interval: usize, // One Candidate out of this number is verified
+	pub rng_seed: Option<u64>, // Seed for the random number generators (for example for random bases in primality tests), None to seed them from entropy
+	pub trial_division_prime_count: usize, // Tier 1 of the checks, how many of the primes after the ones of the primorial are used for trial divisions before the Fermat tests
+	pub confirmation_reps: usize, // Tier 3 of the checks, if not 0, the numbers of the tuples to be outputted are confirmed with GMP's is_probably_prime(confirmation_reps)
+	pub unsieved_primes: usize, // How many of the first primes are not used by the sieve, at least the primorial_number - 1 ones of the primorial. The sieve starts after them, and the small primes after the primorial can then be left to the trial divisions
+	pub profile_eliminations: bool, // For profiling, count the elimination iterations of the sieve per prime magnitude
+	pub exact_range_start: bool, // Start at the first Candidate at or above target_min, instead of after the next primorial multiple
+	pub cross_check: bool, // For debugging, also test every tested number with GMP and record the disagreements with the trial divisions and Fermat test
+	pub check_task_insertion: CheckTaskInsertion,
+	pub memory_budget: Option<usize>, // In bytes, if the estimated memory usage exceeds it, the prime table and then the number of workers are reduced
+	pub sieve_workers: usize, // Workers only processing Sieve Tasks
+	pub check_workers: usize, // Workers only processing Check Tasks, they do not need a Sieve buffer
+	pub heartbeat_interval: Option<Duration>, // If set, an Event::Heartbeat is queued at this interval while the workers run
+	pub primality_test: PrimalityTest, // Tier 2 of the checks
+	pub max_factors_per_sieve_task: usize, // If not 0, the Sieve Tasks process at most this number of primorial factors even if the sieve is bigger, so they stay short
+	pub max_held_outputs: usize // While the Outputs are held, the workers stop taking Tasks once the queues contain this number of Outputs
 }
 
 impl Default for Params {
@@ -108,15 +590,151 @@ impl Default for Params {
 			workers: 0,
 			constellation_pattern: vec![],
 			prime_table_limit: 0,
+			prime_count: None,
 			primorial_number: 0,
 			primorial_offset: 0,
-			sieve_size: 0
+			sieve_size: 0,
+			single_sieve_window: false,
+			check_order: CheckOrder::LeftToRight,
+			sieve_buffer_pool_size: 0,
+			verify_sieve: false,
+			verify_sieve_interval: 0,
+			rng_seed: None,
+			trial_division_prime_count: 0,
+			confirmation_reps: 0,
+			unsieved_primes: 0,
+			profile_eliminations: false,
+			exact_range_start: false,
+			cross_check: false,
+			check_task_insertion: CheckTaskInsertion::Front,
+			memory_budget: None,
+			sieve_workers: 0,
+			check_workers: 0,
+			heartbeat_interval: None,
+			primality_test: PrimalityTest::Fermat,
+			max_factors_per_sieve_task: 0,
+			max_held_outputs: 0
+		}
+	}
+}
+
+// Phases reported during the initialization of a Stella instance.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InitEvent {
+	PrimeTableGenerated(usize), // Number of primes generated
+	ModularInversesGenerated
+}
+
+// Events queued by a running Stella instance, popped with pop_event.
+#[derive(Clone, Debug)]
+pub enum Event {
+	Heartbeat(Stats) // Snapshot of the Stats queued every heartbeat_interval even if nothing is found, so a search whose counts still increase is working
+}
+
+// Reasons for which a Stella instance could not be initialized.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InitError {
+	PrimeTableTooSmall {primes: usize, needed: usize}, // The prime table must contain at least primorial_number primes, the primorial_number - 1 first ones for the primorial and at least one for the sieve
+	PrimeCountTooBig {prime_count: usize} // The largest of the prime_count first primes must not exceed MAX_PRIME_TABLE_LIMIT
+}
+
+impl fmt::Display for InitError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			InitError::PrimeTableTooSmall {primes, needed} => write!(f, "The prime table contains {} primes, but at least {} (the Primorial Number) are needed, increase the prime table limit or count.", primes, needed),
+			InitError::PrimeCountTooBig {prime_count} => write!(f, "The prime count {} is too big, the primes must not exceed {} on this machine.", prime_count, MAX_PRIME_TABLE_LIMIT)
+		}
+	}
+}
+
+impl std::error::Error for InitError {}
+
+const TASK_DURATION_BUCKETS: usize = 40;
+// Distribution of the durations of Tasks, accumulated without storing every sample
+// The mean and variance are running moments (Welford's algorithm), and the percentiles are estimated with a histogram of power of 2 µs buckets
+#[derive(Clone, Debug)]
+pub struct TaskDurations {
+	pub count: usize,
+	pub min: f64, // In s, like the other durations
+	pub max: f64,
+	pub mean: f64,
+	m2: f64, // Sum of the squared differences from the mean
+	histogram: [usize; TASK_DURATION_BUCKETS] // Bucket i counts the durations lower than 2^i µs (and at least 2^(i - 1) µs)
+}
+
+impl Default for TaskDurations {
+	fn default() -> TaskDurations {
+		return TaskDurations::new();
+	}
+}
+
+impl TaskDurations {
+	pub fn new() -> TaskDurations {
+		return TaskDurations {
+			count: 0,
+			min: 0f64,
+			max: 0f64,
+			mean: 0f64,
+			m2: 0f64,
+			histogram: [0; TASK_DURATION_BUCKETS]
+		};
+	}
+	
+	fn add(&mut self, duration: f64) {
+		self.min = if self.count == 0 {duration} else {self.min.min(duration)};
+		self.max = self.max.max(duration);
+		self.count += 1;
+		let delta = duration - self.mean;
+		self.mean += delta/(self.count as f64);
+		self.m2 += delta*(duration - self.mean);
+		let microseconds = (duration*1e6) as u64;
+		self.histogram[std::cmp::min((u64::BITS - microseconds.leading_zeros()) as usize, TASK_DURATION_BUCKETS - 1)] += 1;
+	}
+	
+	pub fn variance(&self) -> f64 {
+		if self.count < 2 {return 0f64;}
+		return self.m2/((self.count - 1) as f64);
+	}
+	
+	pub fn standard_deviation(&self) -> f64 {
+		return self.variance().sqrt();
+	}
+	
+	// Estimate of the duration below which the given fraction (like 0.99) of the Tasks were done, the precision is a factor 2
+	pub fn percentile(&self, fraction: f64) -> f64 {
+		let threshold = fraction*(self.count as f64);
+		let mut cumulated_count = 0;
+		for i in 0 .. TASK_DURATION_BUCKETS {
+			cumulated_count += self.histogram[i];
+			if cumulated_count > 0 && (cumulated_count as f64) >= threshold {
+				return f64::min((1u64 << i) as f64/1e6, self.max);
+			}
+		}
+		return self.max;
+	}
+	
+	pub fn merge(&self, other: &TaskDurations) -> TaskDurations {
+		if self.count == 0 {return other.clone();}
+		if other.count == 0 {return self.clone();}
+		let count = self.count + other.count;
+		let delta = other.mean - self.mean;
+		let mut histogram = self.histogram;
+		for (bucket, other_bucket) in histogram.iter_mut().zip(other.histogram.iter()) {
+			*bucket += other_bucket;
 		}
+		return TaskDurations {
+			count: count,
+			min: self.min.min(other.min),
+			max: self.max.max(other.max),
+			mean: self.mean + delta*(other.count as f64)/(count as f64),
+			m2: self.m2 + other.m2 + delta*delta*(self.count as f64)*(other.count as f64)/(count as f64),
+			histogram: histogram
+		};
 	}
 }
 
 // Struct containing relevant statistics of a Stella instance.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Stats {
 	pub prime_table_size: usize,
 	pub prime_table_generation_time: f64,
@@ -126,7 +744,25 @@ pub struct Stats {
 	pub candidates_generated: usize,
 	pub testing_duration: f64,
 	pub candidates_tested: usize,
-	pub tuple_counts: Vec<usize>
+	pub tuple_counts: Vec<usize>,
+	pub position_prime_counts: Vec<usize>,
+	pub candidates_verified: usize, // Candidates verified with verify_sieve
+	pub sieve_verification_failures: usize, // Verified Candidates that were divisible by a sieving prime, should stay 0
+	pub sieve_task_durations: TaskDurations,
+	pub check_task_durations: TaskDurations,
+	pub stale_tasks_discarded: usize, // Tasks popped after their Job was removed
+	pub trial_division_rejections: usize, // Numbers found composite by the trial divisions (tier 1), each one being a Fermat test avoided
+	pub fermat_tests: usize, // Tier 2, Fermat or Euler tests depending on the primality_test parameter
+	pub confirmation_tests: usize, // Tier 3
+	pub confirmation_failures: usize, // Numbers that passed the Fermat test but not the confirmation
+	pub elimination_counts: Vec<usize>, // With profile_eliminations, index b counts the elimination iterations for the sieving primes from 2^b to 2^(b + 1) - 1
+	pub disagreements: Vec<Integer> // With cross_check, numbers for which GMP's test and the trial divisions and Fermat test disagreed
+}
+
+impl Default for Stats {
+	fn default() -> Stats {
+		return Stats::new();
+	}
 }
 
 impl Stats {
@@ -140,72 +776,605 @@ impl Stats {
 			candidates_generated: 0,
 			testing_duration: 0f64,
 			candidates_tested: 0,
-			tuple_counts: vec![]
+			tuple_counts: vec![],
+			position_prime_counts: vec![],
+			candidates_verified: 0,
+			sieve_verification_failures: 0,
+			sieve_task_durations: TaskDurations::new(),
+			check_task_durations: TaskDurations::new(),
+			stale_tasks_discarded: 0,
+			trial_division_rejections: 0,
+			fermat_tests: 0,
+			confirmation_tests: 0,
+			confirmation_failures: 0,
+			elimination_counts: vec![],
+			disagreements: vec![]
+		};
+	}
+	
+	// Ratio of the overall testing speed to the speed that the workers would achieve if they were all as fast as the fastest one and never idle
+	// 1 means a perfect scaling, lower values reveal idle workers (for example waiting for locks or Tasks) or an imbalance between them
+	pub fn parallel_efficiency(&self, worker_stats: &[WorkerStats]) -> f64 {
+		let fastest_speed = worker_stats.iter().map(|worker_stats| worker_stats.busy_testing_speed()).fold(0f64, f64::max);
+		let elapsed = time_since(self.search_start_instant);
+		if fastest_speed <= 0f64 || elapsed <= 0f64 {return 0f64;}
+		return ((self.candidates_tested as f64)/elapsed)/(fastest_speed*(worker_stats.len() as f64));
+	}
+	
+	// Fraction of the time since the start of the search during which the workers of the given role were busy, 0 if there is no such worker
+	// With dedicated workers, a role close to 1 while the other is much lower means that the former is the bottleneck and should get more workers
+	pub fn utilization(&self, worker_stats: &[WorkerStats], role: WorkerRole) -> f64 {
+		let role_worker_stats: Vec<&WorkerStats> = worker_stats.iter().filter(|worker_stats| worker_stats.role == role).collect();
+		let elapsed = time_since(self.search_start_instant);
+		if role_worker_stats.is_empty() || elapsed <= 0f64 {return 0f64;}
+		return role_worker_stats.iter().map(|worker_stats| worker_stats.busy_duration).sum::<f64>()/(elapsed*(role_worker_stats.len() as f64));
+	}
+	
+	// Combines the Stats of several Stella instances. The counters and durations are summed (the count Vecs element-wise, the shorter one being padded with zeros), and the earliest search start is kept.
+	pub fn merge(&self, other: &Stats) -> Stats {
+		return Stats {
+			prime_table_size: std::cmp::max(self.prime_table_size, other.prime_table_size),
+			prime_table_generation_time: self.prime_table_generation_time + other.prime_table_generation_time,
+			modular_inverses_generation_time: self.modular_inverses_generation_time + other.modular_inverses_generation_time,
+			search_start_instant: std::cmp::min(self.search_start_instant, other.search_start_instant),
+			sieving_duration: self.sieving_duration + other.sieving_duration,
+			candidates_generated: self.candidates_generated + other.candidates_generated,
+			testing_duration: self.testing_duration + other.testing_duration,
+			candidates_tested: self.candidates_tested + other.candidates_tested,
+			tuple_counts: merged_counts(&self.tuple_counts, &other.tuple_counts),
+			position_prime_counts: merged_counts(&self.position_prime_counts, &other.position_prime_counts),
+			candidates_verified: self.candidates_verified + other.candidates_verified,
+			sieve_verification_failures: self.sieve_verification_failures + other.sieve_verification_failures,
+			sieve_task_durations: self.sieve_task_durations.merge(&other.sieve_task_durations),
+			check_task_durations: self.check_task_durations.merge(&other.check_task_durations),
+			stale_tasks_discarded: self.stale_tasks_discarded + other.stale_tasks_discarded,
+			trial_division_rejections: self.trial_division_rejections + other.trial_division_rejections,
+			fermat_tests: self.fermat_tests + other.fermat_tests,
+			confirmation_tests: self.confirmation_tests + other.confirmation_tests,
+			confirmation_failures: self.confirmation_failures + other.confirmation_failures,
+			elimination_counts: merged_counts(&self.elimination_counts, &other.elimination_counts),
+			disagreements: [self.disagreements.clone(), other.disagreements.clone()].concat()
 		};
 	}
 }
 
-// Struct used by workers for sieving.
-struct Sieve {
-	factors_to_eliminate: Vec<usize>,
-	factors_eliminated: Vec<usize>
+// Struct containing the statistics of a single worker.
+#[derive(Clone, Debug)]
+pub struct WorkerStats {
+	pub worker_id: usize,
+	pub role: WorkerRole,
+	pub tasks_processed: usize,
+	pub busy_duration: f64, // Time in s spent processing Tasks rather than waiting for them
+	pub candidates_tested: usize,
+	pub stats_lock_wait_duration: f64 // Time in s spent waiting for the lock of the Stats while processing Tasks, only measured with the profile_locks feature (0 otherwise)
 }
 
-impl Sieve {
-	pub fn new() -> Sieve {
-		return Sieve {
-			factors_to_eliminate: vec![],
-			factors_eliminated: vec![]
+impl WorkerStats {
+	fn new(worker_id: usize, role: WorkerRole) -> WorkerStats {
+		return WorkerStats {
+			worker_id: worker_id,
+			role: role,
+			tasks_processed: 0,
+			busy_duration: 0f64,
+			candidates_tested: 0,
+			stats_lock_wait_duration: 0f64
 		};
 	}
+	
+	// Testing speed of the worker when it is busy, in candidates/s
+	pub fn busy_testing_speed(&self) -> f64 {
+		if self.busy_duration <= 0f64 {return 0f64;}
+		return (self.candidates_tested as f64)/self.busy_duration;
+	}
 }
 
-// Main structure for the library user, handles a customizable search of Prime Constellations.
-pub struct Stella {
-	params: Params,
-	
-	primes: Arc<Vec<usize>>,
-	modular_inverses: Arc<Vec<usize>>,
-	primorial: Integer,
-	
-	jobs: Arc<Mutex<HashMap<usize, Job>>>,
-	tasks: Arc<Mutex<VecDeque<Task>>>,
-	cv: Arc<Condvar>,
-	
-	stats: Arc<Mutex<Stats>>,
-	output: Arc<Mutex<VecDeque<Output>>>,
+// Struct containing the statistics specific to a Job.
+#[derive(Clone, Debug)]
+pub struct JobStats {
+	pub start_instant: Instant, // When the Job was added
+	pub primorial_factor_max: usize,
+	pub bounded: bool, // False if the primorial factor limit had to be clamped to usize::MAX
+	pub primorial_factor_start: usize, // Not 0 if the Job was resumed by load_state, the factors below were sieved before
+	pub primorial_factors_sieved: usize,
+	pub candidates_tested: usize,
+	pub tuple_counts: Vec<usize>,
+	pub position_prime_counts: Vec<usize>
 }
 
-impl Stella {
-	pub fn new() -> Stella {
-		return Stella {
-			params: Params::default(),
-			primes: Arc::new(vec![]),
-			modular_inverses: Arc::new(vec![]),
-			primorial: Integer::from(1),
-			jobs: Arc::new(Mutex::new(HashMap::new())),
-			tasks: Arc::new(Mutex::new(VecDeque::new())),
-			cv: Arc::new(Condvar::new()),
-			stats: Arc::new(Mutex::new(Stats::new())),
-			output: Arc::new(Mutex::new(VecDeque::new()))
+impl JobStats {
+	fn new(pattern_length: usize) -> JobStats {
+		return JobStats {
+			start_instant: Instant::now(),
+			primorial_factor_max: 0,
+			bounded: true,
+			primorial_factor_start: 0,
+			primorial_factors_sieved: 0,
+			candidates_tested: 0,
+			tuple_counts: vec![0; pattern_length + 1],
+			position_prime_counts: vec![0; pattern_length]
 		};
 	}
 	
-	pub fn params(&self) -> Params {
-		return self.params.clone();
+	// Fraction of the primorial factors range that was sieved (the checks of the last Candidates may still be pending)
+	pub fn progress(&self) -> f64 {
+		if self.primorial_factor_max == 0 { // Point search
+			return if self.candidates_tested > 0 {1f64} else {0f64};
+		}
+		return f64::min(1f64, (self.primorial_factors_sieved as f64)/(self.primorial_factor_max as f64));
 	}
 	
-	pub fn set_params(&mut self, params: Params) -> () {
-		if params.workers == 0 {
-			self.params.workers = available_parallelism().unwrap().get();
+	// Estimated remaining time from the average sieving speed since the Job was added
+	pub fn eta(&self) -> Option<Duration> {
+		if !self.bounded || self.primorial_factors_sieved <= self.primorial_factor_start {
+			return None;
 		}
-		
-		else {
-			self.params.workers = params.workers;
+		let rate = ((self.primorial_factors_sieved - self.primorial_factor_start) as f64)/time_since(self.start_instant);
+		let remaining = self.primorial_factor_max.saturating_sub(self.primorial_factors_sieved) as f64;
+		return Some(Duration::from_secs_f64(remaining/rate));
+	}
+	
+	fn add(&mut self, other: &JobStats) {
+		self.candidates_tested += other.candidates_tested;
+		self.tuple_counts = merged_counts(&self.tuple_counts, &other.tuple_counts);
+		self.position_prime_counts = merged_counts(&self.position_prime_counts, &other.position_prime_counts);
+	}
+}
+
+// Which primorial factors of a Job were fully processed, to resume an interrupted search precisely
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+	pub job_id: usize,
+	pub completed_until: usize, // All the primorial factors below were sieved and their Candidates checked
+	pub in_flight: Vec<(usize, usize)> // Primorial factor windows [start, end) being processed, some of their Candidates may be checked already
+}
+
+impl Checkpoint {
+	// Lower bound to use for a new Job resuming the search of a Job with the given target_min, the Candidates being the same as the non completed ones of the original Job
+	// The stride is the one of the Candidates of the Job, given by Stella::candidate_stride (the primorial if there is no alignment)
+	pub fn resumed_target_min(&self, target_min: &Integer, stride: &Integer) -> Integer {
+		return target_min.clone() + stride.clone()*self.completed_until;
+	}
+}
+
+// Serialized in the form job_id=1; completed_until=4096; in_flight=4096-8192,8192-12288
+impl fmt::Display for Checkpoint {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let in_flight: Vec<String> = self.in_flight.iter().map(|(start, end)| format!("{}-{}", start, end)).collect();
+		return write!(f, "job_id={}; completed_until={}; in_flight={}", self.job_id, self.completed_until, in_flight.join(","));
+	}
+}
+
+impl FromStr for Checkpoint {
+	type Err = String;
+	fn from_str(s: &str) -> Result<Checkpoint, String> {
+		let (mut job_id, mut completed_until, mut in_flight) = (None, None, vec![]);
+		for field in s.split(';') {
+			let (key, value) = field.trim().split_once('=').ok_or(format!("Invalid Checkpoint field {}.", field.trim()))?;
+			let invalid_value = || format!("Invalid Checkpoint value {} for {}.", value, key);
+			match key {
+				"job_id" => {job_id = Some(value.parse::<usize>().map_err(|_| invalid_value())?);}
+				"completed_until" => {completed_until = Some(value.parse::<usize>().map_err(|_| invalid_value())?);}
+				"in_flight" => {
+					for window in value.split(',').filter(|window| !window.is_empty()) {
+						let (start, end) = window.split_once('-').ok_or_else(invalid_value)?;
+						in_flight.push((start.parse::<usize>().map_err(|_| invalid_value())?, end.parse::<usize>().map_err(|_| invalid_value())?));
+					}
+				}
+				_ => {return Err(format!("Unknown Checkpoint field {}.", key));}
+			}
 		}
-		if params.constellation_pattern.len() == 0 { // Pick a default pattern if none was chosen
-			self.params.constellation_pattern = vec![0, 2, 6, 8, 12, 18, 20];
-			self.params.primorial_offset = DEFAULT_PRIMORIAL_OFFSETS.iter().find(|&&x| x.0 == &self.params.constellation_pattern).unwrap().1;
+		return Ok(Checkpoint {
+			job_id: job_id.ok_or("Missing job_id in the Checkpoint.")?,
+			completed_until: completed_until.ok_or("Missing completed_until in the Checkpoint.")?,
+			in_flight: in_flight
+		});
+	}
+}
+
+// Line of a saved state describing a Job and where to resume it, in the form id=1; pattern=0,2,6; ...; completed_until=4096
+fn job_state(job: &Job, completed_until: usize) -> String {
+	let pattern: Vec<String> = job.pattern.iter().map(|offset| offset.to_string()).collect();
+	let pattern_min: Vec<String> = job.pattern_min.iter().map(|required| required.to_string()).collect();
+	return format!("id={}; pattern={}; target_min={}; target_max={}; k_min={}; pattern_min={}; dedup_outputs={}; alignment={}; skip_primality_test={}; partial_output_threshold={}; min_output_spacing={}; factor_stride={}; completed_until={}",
+		job.id,
+		pattern.join(","),
+		job.target_min,
+		job.target_max,
+		job.k_min,
+		pattern_min.join(","),
+		job.dedup_outputs,
+		job.alignment.as_ref().map_or("none".to_string(), |alignment| alignment.to_string()),
+		job.skip_primality_test,
+		job.partial_output_threshold,
+		job.min_output_spacing,
+		job.factor_stride.map_or("none".to_string(), |(stride, offset)| format!("{},{}", stride, offset)),
+		completed_until);
+}
+
+fn parse_job_state(s: &str) -> Result<(Job, usize), String> {
+	let mut job = Job {
+		id: 0,
+		clear_previous_jobs: false,
+		pattern: vec![],
+		target_min: Integer::from(0),
+		target_max: Integer::from(0),
+		k_min: 0,
+		pattern_min: vec![],
+		dedup_outputs: false,
+		alignment: None,
+		skip_primality_test: false,
+		partial_output_threshold: 0,
+		min_output_spacing: Integer::from(0),
+		factor_stride: None
+	};
+	let mut completed_until = None;
+	for field in s.split(';') {
+		let (key, value) = field.trim().split_once('=').ok_or(format!("Invalid Job field {}.", field.trim()))?;
+		let invalid_value = || format!("Invalid Job value {} for {}.", value, key);
+		let list = || value.split(',').filter(|element| !element.is_empty());
+		match key {
+			"id" => {job.id = value.parse::<usize>().map_err(|_| invalid_value())?;}
+			"pattern" => {job.pattern = list().map(|offset| offset.parse::<isize>()).collect::<Result<Vec<isize>, _>>().map_err(|_| invalid_value())?;}
+			"target_min" => {job.target_min = value.parse::<Integer>().map_err(|_| invalid_value())?;}
+			"target_max" => {job.target_max = value.parse::<Integer>().map_err(|_| invalid_value())?;}
+			"k_min" => {job.k_min = value.parse::<usize>().map_err(|_| invalid_value())?;}
+			"pattern_min" => {job.pattern_min = list().map(|required| required.parse::<bool>()).collect::<Result<Vec<bool>, _>>().map_err(|_| invalid_value())?;}
+			"dedup_outputs" => {job.dedup_outputs = value.parse::<bool>().map_err(|_| invalid_value())?;}
+			"alignment" => {job.alignment = if value == "none" {None} else {Some(value.parse::<Integer>().map_err(|_| invalid_value())?)};}
+			"skip_primality_test" => {job.skip_primality_test = value.parse::<bool>().map_err(|_| invalid_value())?;}
+			"partial_output_threshold" => {job.partial_output_threshold = value.parse::<usize>().map_err(|_| invalid_value())?;}
+			"min_output_spacing" => {job.min_output_spacing = value.parse::<Integer>().map_err(|_| invalid_value())?;}
+			"factor_stride" => {
+				job.factor_stride = if value == "none" {None} else {
+					let (stride, offset) = value.split_once(',').ok_or_else(invalid_value)?;
+					Some((stride.parse::<usize>().map_err(|_| invalid_value())?, offset.parse::<usize>().map_err(|_| invalid_value())?))
+				};
+			}
+			"completed_until" => {completed_until = Some(value.parse::<usize>().map_err(|_| invalid_value())?);}
+			_ => {return Err(format!("Unknown Job field {}.", key));}
+		}
+	}
+	return Ok((job, completed_until.ok_or("Missing completed_until in the Job state.")?));
+}
+
+// Summary of the state of a Stella instance, to diagnose a stuck or misbehaving search (for example when filing a bug)
+#[derive(Clone, Debug)]
+pub struct DebugSnapshot {
+	pub prime_table_size: usize,
+	pub largest_prime: Option<usize>,
+	pub primorial_bits: u32,
+	pub active_jobs: Vec<usize>, // Ids, sorted
+	pub queued_sieve_tasks: usize,
+	pub queued_check_tasks: usize,
+	pub busy_workers: usize,
+	pub queued_outputs: usize,
+	pub stats: Stats
+}
+
+impl fmt::Display for DebugSnapshot {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "Stella {}", env!("CARGO_PKG_VERSION"))?;
+		writeln!(f, "Prime table: {} primes, largest {:?}", self.prime_table_size, self.largest_prime)?;
+		writeln!(f, "Primorial: {} bits", self.primorial_bits)?;
+		writeln!(f, "Active Jobs: {:?}", self.active_jobs)?;
+		writeln!(f, "Queued Tasks: {} Sieve, {} Check", self.queued_sieve_tasks, self.queued_check_tasks)?;
+		writeln!(f, "Busy workers: {}", self.busy_workers)?;
+		writeln!(f, "Queued outputs: {}", self.queued_outputs)?;
+		writeln!(f, "Search duration: {:.1} s", time_since(self.stats.search_start_instant))?;
+		writeln!(f, "Sieving: {} Candidates generated in {:.2} s", self.stats.candidates_generated, self.stats.sieving_duration)?;
+		writeln!(f, "Testing: {} Candidates tested in {:.2} s", self.stats.candidates_tested, self.stats.testing_duration)?;
+		writeln!(f, "Tuple counts: {:?}", self.stats.tuple_counts)?;
+		return write!(f, "Stale Tasks discarded: {}, sieve verification failures: {}", self.stats.stale_tasks_discarded, self.stats.sieve_verification_failures);
+	}
+}
+
+// Sieve window of a Job, complete once sieved and once all its Check Tasks were done
+struct SieveWindow {
+	end: usize,
+	sieved: bool,
+	pending_checks: usize
+}
+
+// Tracks the processing of the sieve windows of a Job in order to make Checkpoints
+struct JobWindows {
+	completed_until: usize,
+	windows: BTreeMap<usize, SieveWindow> // Indexed by the primorial factor start of the windows
+}
+
+impl JobWindows {
+	fn new() -> JobWindows {
+		return JobWindows {
+			completed_until: 0,
+			windows: BTreeMap::new()
+		};
+	}
+	
+	fn start_sieve(&mut self, start: usize, end: usize) {
+		self.windows.insert(start, SieveWindow {end: end, sieved: false, pending_checks: 0});
+	}
+	
+	// Must be called before pushing the Check Task, so it cannot be finished before being counted
+	fn add_check(&mut self, start: usize) {
+		if let Some(window) = self.windows.get_mut(&start) {
+			window.pending_checks += 1;
+		}
+	}
+	
+	fn finish_sieve(&mut self, start: usize) {
+		if let Some(window) = self.windows.get_mut(&start) {
+			window.sieved = true;
+		}
+		self.advance();
+	}
+	
+	fn finish_check(&mut self, start: usize) {
+		if let Some(window) = self.windows.get_mut(&start) {
+			window.pending_checks = window.pending_checks.saturating_sub(1);
+		}
+		self.advance();
+	}
+	
+	// Forget the completed windows at the beginning of the range
+	fn advance(&mut self) {
+		while let Some(entry) = self.windows.first_entry() {
+			if !entry.get().sieved || entry.get().pending_checks > 0 {
+				break;
+			}
+			self.completed_until = std::cmp::max(self.completed_until, entry.remove().end);
+		}
+	}
+	
+	fn checkpoint(&self, job_id: usize) -> Checkpoint {
+		return Checkpoint {
+			job_id: job_id,
+			completed_until: self.completed_until,
+			in_flight: self.windows.iter().map(|(&start, window)| (start, window.end)).collect()
+		};
+	}
+}
+
+// Bloom filter of reported base numbers, to not report them again across runs, can give false positives (a number never inserted seen as already reported) but not false negatives
+#[derive(Clone, Debug, PartialEq)]
+pub struct BloomFilter {
+	bits: Vec<u64>,
+	hashes: u32
+}
+
+impl BloomFilter {
+	// Filter of the given number of bits (rounded up to a multiple of 64), setting that many bits for each number
+	// A filter needs at least one bit and one hash, so 0 is replaced by 1 for both
+	pub fn new(bits: usize, hashes: u32) -> BloomFilter {
+		return BloomFilter {
+			bits: vec![0 ; bits.max(1).div_ceil(64)],
+			hashes: hashes.max(1)
+		};
+	}
+	
+	// Filter with the optimal size and number of hashes for the given false positive rate once it contains the given number of numbers
+	pub fn with_capacity(numbers: usize, false_positive_rate: f64) -> BloomFilter {
+		let ln2 = std::f64::consts::LN_2;
+		let bits = (-(numbers.max(1) as f64)*false_positive_rate.ln()/(ln2*ln2)).ceil() as usize;
+		let hashes = ((bits as f64)/(numbers.max(1) as f64)*ln2).round() as u32;
+		return BloomFilter::new(bits.max(64), hashes.max(1));
+	}
+	
+	// Bit indexes of n, by double hashing
+	fn indexes(&self, n: &Integer) -> Vec<usize> {
+		let h1 = fnv1a_64(&n.to_digits::<u8>(Order::Lsf));
+		let h2 = fnv1a_64(&h1.to_le_bytes()) | 1;
+		let bit_count = 64*self.bits.len() as u64;
+		return (0 .. self.hashes as u64).map(|i| (h1.wrapping_add(i.wrapping_mul(h2)) % bit_count) as usize).collect();
+	}
+	
+	pub fn contains(&self, n: &Integer) -> bool {
+		return self.indexes(n).iter().all(|&index| self.bits[index/64] & (1 << (index % 64)) != 0);
+	}
+	
+	// Returns whether n was not already (seemingly) in the filter
+	pub fn insert(&mut self, n: &Integer) -> bool {
+		let mut inserted = false;
+		for index in self.indexes(n) {
+			if self.bits[index/64] & (1 << (index % 64)) == 0 {
+				self.bits[index/64] |= 1 << (index % 64);
+				inserted = true;
+			}
+		}
+		return inserted;
+	}
+	
+	// Serialization to save the filter between runs: the number of hashes then the bits, in little endian
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = self.hashes.to_le_bytes().to_vec();
+		for word in &self.bits {
+			bytes.extend_from_slice(&word.to_le_bytes());
+		}
+		return bytes;
+	}
+	
+	pub fn from_bytes(bytes: &[u8]) -> Option<BloomFilter> {
+		if bytes.len() < 12 || !(bytes.len() - 4).is_multiple_of(8) {
+			return None;
+		}
+		let hashes = u32::from_le_bytes(bytes[0 .. 4].try_into().unwrap());
+		if hashes == 0 {
+			return None;
+		}
+		let bits = bytes[4 ..].chunks(8).map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap())).collect();
+		return Some(BloomFilter {bits: bits, hashes: hashes});
+	}
+}
+
+// Whether the Outputs are held and their queues reached max_held_outputs, the workers then wait for release_outputs before taking new Tasks
+fn held_outputs_full(outputs_held: &AtomicBool, output: &Mutex<VecDeque<Output>>, partial_output: &Mutex<VecDeque<Output>>, max_held_outputs: usize) -> bool {
+	return outputs_held.load(Ordering::SeqCst) && output.lock().unwrap().len() + partial_output.lock().unwrap().len() >= max_held_outputs;
+}
+
+// Queues the Event, dropping the oldest one if the queue is full
+fn push_event(events: &Mutex<VecDeque<Event>>, dropped_events: &AtomicUsize, event: Event) -> () {
+	let mut events = events.lock().unwrap();
+	if events.len() >= MAX_QUEUED_EVENTS {
+		events.pop_back();
+		dropped_events.fetch_add(1, Ordering::SeqCst);
+	}
+	events.push_front(event);
+}
+
+// Whether n is not in the dedup filter, if any (it is only inserted once the output is emitted)
+fn unreported_output(dedup_filter: &Option<BloomFilter>, n: &Integer) -> bool {
+	return match dedup_filter {
+		Some(filter) => !filter.contains(n),
+		None => true
+	};
+}
+
+// Whether n is far enough from the last emitted output of the Job to be emitted, with min_output_spacing (it only becomes the last emitted output once emitted)
+fn spaced_output(last_output_ns: &HashMap<usize, Integer>, job: &Job, n: &Integer) -> bool {
+	if job.min_output_spacing == 0 {
+		return true;
+	}
+	return match last_output_ns.get(&job.id) {
+		Some(last_output_n) => Integer::from(n - last_output_n).abs() >= job.min_output_spacing,
+		None => true
+	};
+}
+
+// Index of the bucket of p in the elimination profile, floor(log2(p))
+fn prime_magnitude(p: usize) -> usize {
+	return (usize::BITS - 1 - p.leading_zeros()) as usize;
+}
+
+// Memory used by the prime table, the modular inverses and the Sieve buffers (the temporary table of the prime generation is not counted)
+fn estimated_memory_usage(params: &Params) -> usize {
+	let prime_count = params.prime_count.unwrap_or_else(|| prime_count_estimate(params.prime_table_limit));
+	let sieve_buffers = match params.sieve_buffer_pool_size > 0 && params.sieve_buffer_pool_size < params.workers {
+		true => params.sieve_buffer_pool_size,
+		false => params.workers - params.check_workers
+	};
+	let sieve_buffer_size = params.constellation_pattern.len()*prime_count*size_of::<usize>() + params.sieve_size/8;
+	return 2*prime_count*size_of::<usize>() + sieve_buffers*sieve_buffer_size;
+}
+
+// Element-wise sum of two count Vecs of possibly different lengths
+fn merged_counts(a: &[usize], b: &[usize]) -> Vec<usize> {
+	let mut counts = vec![0; std::cmp::max(a.len(), b.len())];
+	for i in 0 .. a.len() {counts[i] += a[i];}
+	for i in 0 .. b.len() {counts[i] += b[i];}
+	return counts;
+}
+
+// Struct used by workers for sieving.
+struct Sieve {
+	factors_to_eliminate: Vec<usize>,
+	factors_eliminated: Vec<usize>
+}
+
+impl Sieve {
+	fn new(factors_to_eliminate_size: usize, sieve_words: usize) -> Sieve {
+		return Sieve {
+			factors_to_eliminate: vec![0 ; factors_to_eliminate_size],
+			factors_eliminated: vec![0 ; sieve_words]
+		};
+	}
+}
+
+// Prime table and modular inverses of the primorial modulo these primes
+type PrimeTables = (Arc<Vec<usize>>, Arc<Vec<usize>>);
+
+// Main structure for the library user, handles a customizable search of Prime Constellations.
+pub struct Stella {
+	params: Params,
+	
+	primes: Arc<Vec<usize>>,
+	modular_inverses: Arc<Vec<usize>>,
+	tables: Arc<Mutex<PrimeTables>>, // Prime table and modular inverses read by the workers at the start of each Task, swapped together so they can be changed without restarting the workers
+	primorial: Integer,
+	
+	jobs: Arc<Mutex<HashMap<usize, Job>>>,
+	tasks: Arc<Mutex<VecDeque<Task>>>,
+	cv: Arc<Condvar>,
+	busy_workers: Arc<Mutex<usize>>, // Workers currently processing a Task, must be locked after tasks if both are needed
+	idle_cv: Arc<Condvar>,
+	stopping: Arc<AtomicBool>, // Set to make the workers exit, must be changed under the tasks lock so no worker misses the notification
+	
+	stats: Arc<Mutex<Stats>>,
+	job_stats: Arc<Mutex<HashMap<usize, JobStats>>>,
+	output: Arc<Mutex<VecDeque<Output>>>,
+	partial_output: Arc<Mutex<VecDeque<Output>>>,
+	outputs_held: Arc<AtomicBool>, // Set by hold_outputs, the queues are then not popped
+	output_seq: Arc<AtomicU64>, // Sequence number of the next output, taken under the lock of its queue so the numbers follow the order of the queues
+	reported_factors: Arc<Mutex<HashMap<usize, HashSet<usize>>>>, // Primorial factors of the outputs of the Jobs using dedup_outputs
+	last_output_ns: Arc<Mutex<HashMap<usize, Integer>>>, // Base number of the last emitted output of the Jobs using min_output_spacing
+	dedup_filter: Arc<Mutex<Option<BloomFilter>>>, // Base numbers already reported, possibly in previous runs
+	job_windows: Arc<Mutex<HashMap<usize, JobWindows>>>,
+	worker_stats: Arc<Mutex<Vec<WorkerStats>>>,
+	worker_threads: Vec<thread::JoinHandle<()>>, // Joined by stop_workers, so a stopped worker cannot take the Tasks of a later start
+	stats_threads: Vec<(Arc<AtomicBool>, thread::JoinHandle<()>)>, // Threads of on_stats_interval, with their stop flags
+	events: Arc<Mutex<VecDeque<Event>>>, // At most MAX_QUEUED_EVENTS
+	dropped_events: Arc<AtomicUsize>, // Oldest Events dropped because the queue was full
+	resumed_stats: Option<(Stats, f64)>, // Stats restored by load_state and the search duration when they were saved, applied by start_workers
+}
+
+impl Default for Stella {
+	fn default() -> Stella {
+		return Stella::new();
+	}
+}
+
+impl Stella {
+	pub fn new() -> Stella {
+		return Stella {
+			params: Params::default(),
+			primes: Arc::new(vec![]),
+			modular_inverses: Arc::new(vec![]),
+			tables: Arc::new(Mutex::new((Arc::new(vec![]), Arc::new(vec![])))),
+			primorial: Integer::from(1),
+			jobs: Arc::new(Mutex::new(HashMap::new())),
+			tasks: Arc::new(Mutex::new(VecDeque::new())),
+			cv: Arc::new(Condvar::new()),
+			busy_workers: Arc::new(Mutex::new(0)),
+			idle_cv: Arc::new(Condvar::new()),
+			stopping: Arc::new(AtomicBool::new(false)),
+			stats: Arc::new(Mutex::new(Stats::new())),
+			job_stats: Arc::new(Mutex::new(HashMap::new())),
+			output: Arc::new(Mutex::new(VecDeque::new())),
+			partial_output: Arc::new(Mutex::new(VecDeque::new())),
+			outputs_held: Arc::new(AtomicBool::new(false)),
+			output_seq: Arc::new(AtomicU64::new(0)),
+			reported_factors: Arc::new(Mutex::new(HashMap::new())),
+			last_output_ns: Arc::new(Mutex::new(HashMap::new())),
+			dedup_filter: Arc::new(Mutex::new(None)),
+			job_windows: Arc::new(Mutex::new(HashMap::new())),
+			worker_stats: Arc::new(Mutex::new(vec![])),
+			worker_threads: vec![],
+			stats_threads: vec![],
+			events: Arc::new(Mutex::new(VecDeque::new())),
+			dropped_events: Arc::new(AtomicUsize::new(0)),
+			resumed_stats: None
+		};
+	}
+	
+	pub fn params(&self) -> Params {
+		return self.params.clone();
+	}
+	
+	// Returns possible warnings about the parameters
+	pub fn set_params(&mut self, params: Params) -> Vec<String> {
+		let mut warnings = vec![];
+		if params.workers == 0 {
+			match available_parallelism() {
+				Ok(parallelism) => {self.params.workers = parallelism.get();}
+				Err(error) => {
+					warnings.push(format!("Could not detect the number of threads ({}), using a single worker. Set the number of workers manually to use more.", error).to_string());
+					self.params.workers = 1;
+				}
+			}
+		}
+		else {
+			self.params.workers = params.workers;
+		}
+		if params.constellation_pattern.is_empty() { // Pick a default pattern if none was chosen
+			self.params.constellation_pattern = vec![0, 2, 6, 8, 12, 18, 20];
+			self.params.primorial_offset = default_offset_for(&self.params.constellation_pattern).unwrap();
 		}
 		else {
 			self.params.constellation_pattern = params.constellation_pattern;
@@ -218,16 +1387,42 @@ impl Stella {
 			self.params.primorial_number = params.primorial_number;
 		}
 		
-		if params.prime_table_limit == 0 {
-			self.params.prime_table_limit = 16777216;
-		}
-		else {
-			self.params.prime_table_limit = params.prime_table_limit;
+		match params.prime_count {
+			Some(prime_count) if params.prime_table_limit != 0 || prime_count == 0 => {
+				let reason = match prime_count {
+					0 => "The prime count must not be 0",
+					_ => "Only one of prime_table_limit and prime_count must be set"
+				};
+				if self.params.prime_table_limit == 0 && self.params.prime_count.is_none() { // No previous value
+					warnings.push(format!("{}, using the default prime table limit.", reason).to_string());
+					self.params.prime_table_limit = 16777216;
+				}
+				else {
+					warnings.push(format!("{}, keeping the previous prime table limit or count.", reason).to_string());
+				}
+			}
+			Some(_) => {
+				self.params.prime_table_limit = 0; // Set once the primes are generated
+				self.params.prime_count = params.prime_count;
+			}
+			None => {
+				if params.prime_table_limit == 0 {
+					self.params.prime_table_limit = 16777216;
+				}
+				else if params.prime_table_limit > MAX_PRIME_TABLE_LIMIT {
+					warnings.push(format!("The prime table limit {} exceeds the maximum of {} on this machine, using the maximum.", params.prime_table_limit, MAX_PRIME_TABLE_LIMIT).to_string());
+					self.params.prime_table_limit = MAX_PRIME_TABLE_LIMIT;
+				}
+				else {
+					self.params.prime_table_limit = params.prime_table_limit;
+				}
+				self.params.prime_count = None;
+			}
 		}
 		
 		if params.primorial_offset == 0 { // Pick a default Primorial Offset if none was chosen, if possible
-			match DEFAULT_PRIMORIAL_OFFSETS.iter().find(|&&x| x.0 == &self.params.constellation_pattern) {
-				Some(default_primorial_offset) => {self.params.primorial_offset = default_primorial_offset.1;}
+			match default_offset_for(&self.params.constellation_pattern) {
+				Some(default_primorial_offset) => {self.params.primorial_offset = default_primorial_offset;}
 				None => {panic!("The chosen Constellation Pattern does not have a default Primorial Offset, which must be set manually with the primorial_offset field.");}
 			}
 		}
@@ -238,210 +1433,1477 @@ impl Stella {
 		if params.sieve_size == 0 {
 			self.params.sieve_size = 1 << 25;
 		}
+		else if params.sieve_size < WORD_SIZE {
+			if self.params.sieve_size == 0 { // No previous value
+				self.params.sieve_size = 1 << 25;
+			}
+			warnings.push(format!("The sieve size must be at least the word size ({}), as it is rounded down to a multiple of it, keeping the previous value {}.", WORD_SIZE, self.params.sieve_size).to_string());
+		}
 		else {
 			self.params.sieve_size = (params.sieve_size/WORD_SIZE)*WORD_SIZE;
 		}
+		
+		self.params.single_sieve_window = params.single_sieve_window;
+		self.params.check_order = params.check_order;
+		self.params.sieve_buffer_pool_size = params.sieve_buffer_pool_size;
+		self.params.verify_sieve = params.verify_sieve;
+		if params.verify_sieve_interval == 0 {
+			self.params.verify_sieve_interval = 1000;
+		}
+		else {
+			self.params.verify_sieve_interval = params.verify_sieve_interval;
+		}
+		self.params.rng_seed = params.rng_seed;
+		self.params.confirmation_reps = params.confirmation_reps;
+		if params.unsieved_primes != 0 && params.unsieved_primes < self.params.primorial_number - 1 {
+			warnings.push(format!("The unsieved primes must include the {} primes of the primorial, so unsieved_primes must be 0 or at least {}, keeping the previous value {}.", self.params.primorial_number - 1, self.params.primorial_number - 1, self.params.unsieved_primes).to_string());
+		}
+		else {
+			self.params.unsieved_primes = params.unsieved_primes;
+		}
+		// The primes of the primorial never divide the Candidates, and the sieving primes were already eliminated, so by default only the primes after the primorial excluded from the sieve by unsieved_primes are left to the trial divisions
+		let unsieved_primes_after_primorial = first_sieving_prime_index(&self.params) - (self.params.primorial_number - 1);
+		if params.trial_division_prime_count == 0 {
+			self.params.trial_division_prime_count = unsieved_primes_after_primorial;
+		}
+		else {
+			if params.trial_division_prime_count < unsieved_primes_after_primorial {
+				warnings.push(format!("The trial divisions by {} primes do not cover the {} unsieved primes after the ones of the primorial, the numbers divisible by the other ones will be left to the Fermat tests.", params.trial_division_prime_count, unsieved_primes_after_primorial).to_string());
+			}
+			self.params.trial_division_prime_count = params.trial_division_prime_count;
+		}
+		self.params.profile_eliminations = params.profile_eliminations;
+		self.params.exact_range_start = params.exact_range_start;
+		self.params.cross_check = params.cross_check;
+		self.params.check_task_insertion = params.check_task_insertion;
+		let dedicated_workers_error = |sieve_workers: usize, check_workers: usize, workers: usize| -> Option<String> {
+			if sieve_workers + check_workers > workers {
+				return Some(format!("There are more dedicated workers ({} sieve and {} check) than workers ({})", sieve_workers, check_workers, workers).to_string());
+			}
+			if sieve_workers + check_workers == workers && (sieve_workers == 0 || check_workers == 0) {
+				return Some("All the workers are dedicated to the same Tasks, so the other ones would never be processed".to_string());
+			}
+			return None;
+		};
+		match dedicated_workers_error(params.sieve_workers, params.check_workers, self.params.workers) {
+			Some(error) => {
+				if dedicated_workers_error(self.params.sieve_workers, self.params.check_workers, self.params.workers).is_some() { // The previous values do not fit the new number of workers
+					self.params.sieve_workers = 0;
+					self.params.check_workers = 0;
+				}
+				warnings.push(format!("{}, keeping the previous values ({} sieve and {} check).", error, self.params.sieve_workers, self.params.check_workers).to_string());
+			}
+			None => {
+				self.params.sieve_workers = params.sieve_workers;
+				self.params.check_workers = params.check_workers;
+			}
+		}
+		if params.heartbeat_interval.is_some_and(|interval| interval.is_zero()) {
+			warnings.push(format!("The heartbeat interval must not be zero, keeping the previous value {:?}.", self.params.heartbeat_interval).to_string());
+		}
+		else {
+			self.params.heartbeat_interval = params.heartbeat_interval;
+		}
+		self.params.primality_test = params.primality_test;
+		if params.max_factors_per_sieve_task > self.params.sieve_size {
+			warnings.push(format!("The maximum number of primorial factors per Sieve Task {} exceeds the sieve size {}, so it has no effect.", params.max_factors_per_sieve_task, self.params.sieve_size).to_string());
+		}
+		self.params.max_factors_per_sieve_task = params.max_factors_per_sieve_task;
+		if params.max_held_outputs == 0 {
+			self.params.max_held_outputs = DEFAULT_MAX_HELD_OUTPUTS;
+		}
+		else {
+			self.params.max_held_outputs = params.max_held_outputs;
+		}
+		self.params.memory_budget = params.memory_budget;
+		if let Some(memory_budget) = params.memory_budget {
+			let initial_memory_usage = estimated_memory_usage(&self.params);
+			if initial_memory_usage > memory_budget {
+				// First halve the prime table, as a smaller one only makes the sieve eliminate less, then remove workers
+				while estimated_memory_usage(&self.params) > memory_budget {
+					match self.params.prime_count {
+						Some(prime_count) if prime_count/2 >= prime_count_estimate(MIN_BUDGET_PRIME_TABLE_LIMIT) => {self.params.prime_count = Some(prime_count/2);}
+						None if self.params.prime_table_limit/2 >= MIN_BUDGET_PRIME_TABLE_LIMIT => {self.params.prime_table_limit /= 2;}
+						_ => break
+					}
+				}
+				while estimated_memory_usage(&self.params) > memory_budget && self.params.workers > std::cmp::max(self.params.sieve_workers + self.params.check_workers, 1) {
+					self.params.workers -= 1;
+				}
+				let prime_table = match self.params.prime_count {
+					Some(prime_count) => format!("prime count {}", prime_count),
+					None => format!("prime table limit {}", self.params.prime_table_limit)
+				};
+				warnings.push(format!("The estimated memory usage of {} bytes exceeds the budget of {} bytes, reduced to {} bytes with a {} and {} worker(s).", initial_memory_usage, memory_budget, estimated_memory_usage(&self.params), prime_table, self.params.workers));
+				if estimated_memory_usage(&self.params) > memory_budget {
+					warnings.push("The memory budget is too small to be met, even with the smallest prime table and a single worker.".to_string());
+				}
+			}
+		}
+		return warnings;
+	}
+	
+	// Rough estimate in bytes of the memory used by the prime tables and the Sieve buffers with the current parameters, which dominate for big prime tables
+	pub fn estimated_memory_usage(&self) -> usize {
+		return estimated_memory_usage(&self.params);
 	}
 	
 	pub fn primorial(&self) -> Integer {
 		return self.primorial.clone();
 	}
 	
-	pub fn init(&mut self) -> () {
+	// Size of the primorial, to compare with the target size when choosing the Primorial Number, the init method must have been called before
+	pub fn primorial_bits(&self) -> u32 {
+		return self.primorial.significant_bits();
+	}
+	
+	// Number of prime factors of the primorial, the primorial_number - 1 first primes
+	pub fn primorial_prime_count(&self) -> usize {
+		return self.params.primorial_number.saturating_sub(1);
+	}
+	
+	// Largest prime used by the sieve, None if the init method was not called or if the table does not contain primes beyond the unsieved ones
+	// Numbers of the pattern whose smallest factor is greater than this one cannot be eliminated by the sieve
+	pub fn largest_sieve_prime(&self) -> Option<usize> {
+		return self.primes.get(first_sieving_prime_index(&self.params) ..).and_then(|sieving_primes| sieving_primes.last()).cloned();
+	}
+	
+	// Random number generator seeded with rng_seed, so the random sequences are the same run-to-run, or from entropy if no seed was set
+	pub fn rand_state(&self) -> RandState<'static> {
+		let mut rand = RandState::new();
+		rand.seed(&Integer::from(self.params.rng_seed.unwrap_or_else(entropy_seed)));
+		return rand;
+	}
+	
+	// Summary of the configuration producing the search results, in the form key=value; key=value;..., ending with a hash of the other fields.
+	// The fields are stable across runs, so two runs can be confirmed to be identical by comparing the fingerprints or just the hashes.
+	pub fn configuration_fingerprint(&self) -> String {
+		let pattern: Vec<String> = self.params.constellation_pattern.iter().map(|offset| offset.to_string()).collect();
+		let summary = format!("stella={}; constellation_pattern={}; prime_table_limit={}; prime_count={}; primorial_number={}; primorial_offset={}; exact_range_start={}; unsieved_primes={}; sieve_size={}; primorial={}",
+			env!("CARGO_PKG_VERSION"),
+			pattern.join(","),
+			self.params.prime_table_limit,
+			self.params.prime_count.map_or("none".to_string(), |prime_count| prime_count.to_string()),
+			self.params.primorial_number,
+			self.params.primorial_offset,
+			self.params.exact_range_start,
+			self.params.unsieved_primes,
+			self.params.sieve_size,
+			self.primorial);
+		let hash = fnv1a_64(summary.as_bytes());
+		return format!("{}; hash={:016x}", summary, hash);
+	}
+	
+	pub fn init(&mut self) -> Result<(), InitError> {
+		return self.init_with_progress(&|_| {});
+	}
+	
+	// Same as init, but calls the progress function each time an initialization phase is done, for example to show the progress in an user interface
+	pub fn init_with_progress(&mut self, progress: &dyn Fn(InitEvent)) -> Result<(), InitError> {
 		let mut start_instant = Instant::now();
-		self.primes = Arc::new(generate_primes(self.params.prime_table_limit));
+		let primes = match self.params.prime_count {
+			Some(prime_count) => {
+				// The nth prime exceeds n(ln(n) + ln(ln(n)) - 1) for n >= 2, so a much too big count is rejected without generating the primes
+				let count = prime_count as f64;
+				if prime_count >= 2 && count*(count.ln() + count.ln().ln() - 1f64) > MAX_PRIME_TABLE_LIMIT as f64 {
+					return Err(InitError::PrimeCountTooBig {prime_count: prime_count});
+				}
+				let primes = generate_first_n_primes(prime_count);
+				if *primes.last().unwrap() > MAX_PRIME_TABLE_LIMIT {
+					return Err(InitError::PrimeCountTooBig {prime_count: prime_count});
+				}
+				primes
+			}
+			None => generate_primes(self.params.prime_table_limit)
+		};
+		if primes.len() < self.params.primorial_number {
+			return Err(InitError::PrimeTableTooSmall {primes: primes.len(), needed: self.params.primorial_number});
+		}
+		if self.params.prime_count.is_some() {
+			self.params.prime_table_limit = *primes.last().unwrap(); // So the table can be generated again with the limit
+		}
+		self.primes = Arc::new(primes);
 		self.stats.lock().unwrap().prime_table_generation_time = time_since(start_instant);
 		self.stats.lock().unwrap().prime_table_size = self.primes.len();
+		progress(InitEvent::PrimeTableGenerated(self.primes.len()));
 		self.primorial = primorial(&self.primes, self.params.primorial_number);
 		start_instant = Instant::now();
 		self.modular_inverses = Arc::new(compute_modular_inverses(&self.primorial, &self.primes));
 		self.stats.lock().unwrap().modular_inverses_generation_time = time_since(start_instant);
+		*self.tables.lock().unwrap() = (self.primes.clone(), self.modular_inverses.clone());
+		progress(InitEvent::ModularInversesGenerated);
+		return Ok(());
+	}
+	
+	// Grows the prime table up to the new limit (smaller limits are ignored), the init method must have been called before
+	// Running workers pick the new table at their next Task, so there is no need to restart them
+	// Returns an error if the limit exceeds MAX_PRIME_TABLE_LIMIT, the table being then unchanged
+	pub fn extend_prime_table(&mut self, prime_table_limit: usize) -> Result<(), String> {
+		if prime_table_limit <= self.params.prime_table_limit {
+			return Ok(());
+		}
+		if prime_table_limit > MAX_PRIME_TABLE_LIMIT {
+			return Err(format!("The prime table limit {} exceeds the maximum of {} on this machine.", prime_table_limit, MAX_PRIME_TABLE_LIMIT));
+		}
+		let mut start_instant = Instant::now();
+		let primes = Arc::new(generate_primes(prime_table_limit));
+		self.stats.lock().unwrap().prime_table_generation_time += time_since(start_instant);
+		start_instant = Instant::now();
+		let modular_inverses = Arc::new(compute_modular_inverses(&self.primorial, &primes)); // The primorial stays the same, as the first primes do not change
+		self.stats.lock().unwrap().modular_inverses_generation_time += time_since(start_instant);
+		self.stats.lock().unwrap().prime_table_size = primes.len();
+		self.params.prime_table_limit = prime_table_limit;
+		self.params.prime_count = None; // The table is now defined by its limit
+		self.primes = primes;
+		self.modular_inverses = modular_inverses;
+		*self.tables.lock().unwrap() = (self.primes.clone(), self.modular_inverses.clone());
+		return Ok(());
 	}
 	
 	pub fn start_workers(&mut self) -> () {
+		self.spawn_workers();
+		if let Some(heartbeat_interval) = self.params.heartbeat_interval { // Stopped by stop_workers like the other stats threads
+			let events = self.events.clone();
+			let dropped_events = self.dropped_events.clone();
+			let _ = self.on_stats_interval(heartbeat_interval, Arc::new(move |stats: Stats| { // Cannot fail, set_params does not accept a zero interval
+				push_event(&events, &dropped_events, Event::Heartbeat(stats));
+			}));
+		}
+	}
+	
+	// Only starts the worker threads, for find_best and calibrate which must not touch the stats threads
+	fn spawn_workers(&mut self) -> () {
 		let workers = self.params.workers;
+		{
+			let _tasks = self.tasks.lock().unwrap();
+			self.stopping.store(false, Ordering::SeqCst);
+		}
+		let factors_to_eliminate_size = self.params.constellation_pattern.len()*self.primes.len();
+		let sieve_words = self.params.sieve_size/WORD_SIZE;
+		// With a pool smaller than the number of workers, the Sieve buffers are shared and borrowed by the Sieve Tasks
+		let pooled_sieves = self.params.sieve_buffer_pool_size > 0 && self.params.sieve_buffer_pool_size < workers;
+		let sieve_pool = Arc::new(Mutex::new(Vec::new()));
+		let sieve_pool_cv = Arc::new(Condvar::new());
+		if pooled_sieves {
+			for _ in 0 .. self.params.sieve_buffer_pool_size {
+				sieve_pool.lock().unwrap().push(Sieve::new(factors_to_eliminate_size, sieve_words));
+			}
+		}
+		self.stats.lock().unwrap().search_start_instant = Instant::now();
+		self.stats.lock().unwrap().sieving_duration = 0f64;
+		self.stats.lock().unwrap().candidates_generated = 0;
+		self.stats.lock().unwrap().testing_duration = 0f64;
+		self.stats.lock().unwrap().candidates_tested = 0;
+		self.stats.lock().unwrap().tuple_counts = vec![0; self.params.constellation_pattern.len() + 1];
+		self.stats.lock().unwrap().position_prime_counts = vec![0; self.params.constellation_pattern.len()];
+		self.stats.lock().unwrap().candidates_verified = 0;
+		self.stats.lock().unwrap().sieve_verification_failures = 0;
+		self.stats.lock().unwrap().sieve_task_durations = TaskDurations::new();
+		self.stats.lock().unwrap().check_task_durations = TaskDurations::new();
+		self.stats.lock().unwrap().stale_tasks_discarded = 0;
+		self.stats.lock().unwrap().trial_division_rejections = 0;
+		self.stats.lock().unwrap().fermat_tests = 0;
+		self.stats.lock().unwrap().confirmation_tests = 0;
+		self.stats.lock().unwrap().confirmation_failures = 0;
+		self.stats.lock().unwrap().elimination_counts = vec![];
+		self.stats.lock().unwrap().disagreements = vec![];
+		if let Some((resumed_stats, elapsed)) = self.resumed_stats.take() { // Continue the counts and rates of the saved search
+			let mut stats = self.stats.lock().unwrap();
+			let search_start_instant = Instant::now().checked_sub(Duration::from_secs_f64(elapsed)).unwrap_or(stats.search_start_instant);
+			*stats = Stats {
+				prime_table_size: stats.prime_table_size,
+				prime_table_generation_time: stats.prime_table_generation_time,
+				modular_inverses_generation_time: stats.modular_inverses_generation_time,
+				search_start_instant: search_start_instant,
+				..resumed_stats
+			};
+		}
+		*self.worker_stats.lock().unwrap() = (0 .. workers).map(|worker_id| WorkerStats::new(worker_id, WorkerRole::of(worker_id, &self.params))).collect();
 		for worker_id in 0..workers {
 			let primorial = self.primorial.clone();
-			let primorial_offset = self.params.primorial_offset.clone();
+			let primorial_offset = self.params.primorial_offset;
 			let params = self.params.clone();
 			let constellation_pattern = self.params.constellation_pattern.clone();
-			let primes = self.primes.clone();
-			let modular_inverses = self.modular_inverses.clone();
-			let sieve_size = self.params.sieve_size.clone();
-			let sieve_words = sieve_size/WORD_SIZE;
+			let tables = self.tables.clone();
 			let output = self.output.clone();
+			let partial_output = self.partial_output.clone();
+			let outputs_held = self.outputs_held.clone();
+			let output_seq = self.output_seq.clone();
+			let reported_factors = self.reported_factors.clone();
+			let last_output_ns = self.last_output_ns.clone();
+			let dedup_filter = self.dedup_filter.clone();
+			let job_windows = self.job_windows.clone();
 			let tasks = self.tasks.clone();
 			let cv = self.cv.clone();
-			self.stats.lock().unwrap().search_start_instant = Instant::now();
-			self.stats.lock().unwrap().sieving_duration = 0f64;
-			self.stats.lock().unwrap().candidates_generated = 0;
-			self.stats.lock().unwrap().testing_duration = 0f64;
-			self.stats.lock().unwrap().candidates_tested = 0;
-			self.stats.lock().unwrap().tuple_counts = vec![0; constellation_pattern.len() + 1];
+			let busy_workers = self.busy_workers.clone();
+			let idle_cv = self.idle_cv.clone();
+			let stopping = self.stopping.clone();
 			let stats = self.stats.clone();
-			let mut sieve = Sieve::new();
-			sieve.factors_to_eliminate = vec![0 ; self.params.constellation_pattern.len()*self.primes.len()];
-			sieve.factors_eliminated = vec![0 ; sieve_words];
+			let job_stats = self.job_stats.clone();
+			let worker_stats = self.worker_stats.clone();
+			let role = WorkerRole::of(worker_id, &self.params);
+			let mut own_sieve = match pooled_sieves || role == WorkerRole::Check {
+				true => None,
+				false => Some(Sieve::new(factors_to_eliminate_size, sieve_words))
+			};
+			let sieve_pool = sieve_pool.clone();
+			let sieve_pool_cv = sieve_pool_cv.clone();
 			let jobs = self.jobs.clone();
-			let _ = thread::Builder::new().name(format!("Worker {0}", worker_id)).spawn(move || {
+			let worker_thread = thread::Builder::new().name(format!("Worker {0}", worker_id)).spawn(move || {
 				let mut timer_instant;
 				loop {
 					let task;
 					{
 						let mut tasks = tasks.lock().unwrap();
-						while tasks.is_empty() {
-							tasks = cv.wait(tasks).unwrap();
+						let mut task_index = None; // First Task that the worker can process, the first one of the queue unless it is dedicated
+						while !stopping.load(Ordering::SeqCst) {
+							task_index = tasks.iter().position(|task| role.accepts(&task.t));
+							if task_index.is_some() && !held_outputs_full(&outputs_held, &output, &partial_output, params.max_held_outputs) { // Backpressure, so a long hold does not make the queues grow indefinitely
+								break;
+							}
+							tasks = cv.wait(tasks).unwrap(); // Notified by release_outputs too
+						}
+						if stopping.load(Ordering::SeqCst) {
+							break;
 						}
-						task = tasks.pop_front().unwrap();
+						task = tasks.remove(task_index.unwrap()).unwrap();
+						*busy_workers.lock().unwrap() += 1; // Still under the tasks lock, so wait_idle cannot see an empty queue and no busy worker in between
 					}
-					let job;
+					let task_start_instant = Instant::now();
+					let mut stats_lock_wait = 0f64; // Time spent waiting for the Stats lock, only measured with the profile_locks feature
 					let tmp = jobs.lock().unwrap().clone();
-					match tmp.get(&task.job_id) {
-						Some(tmp) => {job = tmp;}
-						None => {continue;} // Job is no longer current, ignore Task
-					}
+					let job = match tmp.get(&task.job_id) {
+						Some(tmp) => tmp,
+						None => { // Job is no longer current, ignore Task
+							lock_stats(&stats, &mut stats_lock_wait).stale_tasks_discarded += 1;
+							finish_task(&busy_workers, &idle_cv);
+							continue;
+						}
+					};
+					let (primes, modular_inverses) = tables.lock().unwrap().clone();
 					if task.t == TaskType::Sieve {
+						let mut sieve = match own_sieve.take() {
+							Some(sieve) => sieve,
+							None => { // Borrow a buffer from the pool, waiting for one to be released if needed
+								let mut sieve_pool = sieve_pool.lock().unwrap();
+								while sieve_pool.is_empty() {
+									sieve_pool = sieve_pool_cv.wait(sieve_pool).unwrap();
+								}
+								sieve_pool.pop().unwrap()
+							}
+						};
+						if sieve.factors_to_eliminate.len() < constellation_pattern.len()*primes.len() { // The prime table was extended
+							sieve.factors_to_eliminate.resize(constellation_pattern.len()*primes.len(), 0);
+						}
 						timer_instant = Instant::now();
-						let target = job.target_min.clone();
 						let primorial_factor_start = task.primorial_factor_start;
 						let primorial_factor_max = task.primorial_factor_max;
-						let adjusted_primorial_factor_max = std::cmp::min(sieve_size, ((primorial_factor_max - primorial_factor_start)/WORD_SIZE)*WORD_SIZE);
-						// The candidates have the form first_candidate + f × primorial
-						let first_candidate = target.clone() + primorial.clone() - (target.clone() % primorial.clone()) + primorial_offset + primorial_factor_start*primorial.clone();
-						for i in params.primorial_number .. primes.len() {
+						// Both are counts of primorial factors (one bit per factor in the sieve), the last window of a Job can end in the middle of a word
+						let adjusted_primorial_factor_max = std::cmp::min(sieve_window_size(&params), primorial_factor_max - primorial_factor_start);
+						let sieve_words_used = adjusted_primorial_factor_max.div_ceil(WORD_SIZE);
+						if let Some(job_windows) = job_windows.lock().unwrap().get_mut(&job.id) {
+							job_windows.start_sieve(primorial_factor_start, primorial_factor_start + adjusted_primorial_factor_max);
+						}
+						// The candidates have the form first_candidate + f × stride, the stride being the primorial unless the Job has an alignment
+						let (candidates_base, stride) = candidate_lattice(job, &primorial, primorial_offset, params.exact_range_start).unwrap();
+						let first_candidate = candidates_base + primorial_factor_start*stride.clone();
+						for i in first_sieving_prime_index(&params) .. primes.len() {
+							// Inverse of the stride modulo p, 0 if p divides the stride (add_job made sure that no Candidate is then divisible by p)
+							let stride_inverse = match job.alignment {
+								None => modular_inverses[i],
+								Some(_) => Integer::from(stride.mod_u(primes[i] as u32)).invert(&Integer::from(primes[i])).ok().and_then(|inverse| inverse.to_usize()).unwrap_or(0)
+							};
 							for f in 0 .. constellation_pattern.len() {
-								sieve.factors_to_eliminate[constellation_pattern.len()*i + f] = (((primes[i] - ((first_candidate.clone() + constellation_pattern[f]) % primes[i]))*modular_inverses[i]) % primes[i]).to_usize().unwrap();
+								sieve.factors_to_eliminate[constellation_pattern.len()*i + f] = match stride_inverse {
+									0 => usize::MAX, // Nothing to eliminate
+									_ => ((primes[i] - Integer::from(&first_candidate + constellation_pattern[f]).mod_u(primes[i] as u32) as usize)*stride_inverse) % primes[i] // No overflow, as the primes are below MAX_PRIME_TABLE_LIMIT
+								};
 							}
 						}
-						// Make next Sieve Task
-						if primorial_factor_max > adjusted_primorial_factor_max {
+						// For the verification, the remainders of the Candidates modulo the sieving primes are deduced from these ones like for the trial division
+						let sieving_primes = primes.get(first_sieving_prime_index(&params) ..).unwrap_or(&[]);
+						let verification_remainders: Option<(Vec<usize>, Vec<usize>)> = match params.verify_sieve {
+							true => Some((sieving_primes.iter().map(|&p| first_candidate.mod_u(p as u32) as usize).collect(), sieving_primes.iter().map(|&p| stride.mod_u(p as u32) as usize).collect())),
+							false => None
+						};
+						let (mut candidates_extracted, mut candidates_verified, mut sieve_verification_failures) = (0usize, 0usize, 0usize);
+						// Make next Sieve Task, unless the Job was cleared in the meantime
+						if primorial_factor_start + adjusted_primorial_factor_max < primorial_factor_max && jobs.lock().unwrap().contains_key(&job.id) {
 							tasks.lock().unwrap().push_back(Task::new_sieve(job.id, primorial_factor_start + adjusted_primorial_factor_max, primorial_factor_max));
-							cv.notify_all();
+							notify_workers(&cv, &params);
 						}
 						// Eliminate primorial factors of the form p*m + fp for every m*p in the current table.
-						for i in params.primorial_number .. primes.len() {
+						let mut elimination_counts = match params.profile_eliminations {
+							true => vec![0usize; primes.last().map_or(0, |&p| prime_magnitude(p) + 1)],
+							false => vec![]
+						};
+						for i in first_sieving_prime_index(&params) .. primes.len() {
 							for f in 0 .. constellation_pattern.len() {
 								let fp = &mut sieve.factors_to_eliminate[constellation_pattern.len()*i + f];
+								let fp_start = *fp;
 								while *fp < adjusted_primorial_factor_max {
 									sieve.factors_eliminated[*fp/WORD_SIZE] |= 1 << (*fp % WORD_SIZE);
 									*fp += primes[i];
 								}
+								if params.profile_eliminations && *fp != fp_start { // Deduced from the factors, so the loop itself is not slowed down
+									elimination_counts[prime_magnitude(primes[i])] += (*fp - fp_start)/primes[i];
+								}
 							}
 						}
 						// Extract the factors from the sieve
+						if adjusted_primorial_factor_max % WORD_SIZE != 0 { // Mark the factors after the end of the window as eliminated
+							sieve.factors_eliminated[sieve_words_used - 1] |= usize::MAX << (adjusted_primorial_factor_max % WORD_SIZE);
+						}
 						let mut factors_candidates = vec![];
-						for i in params.primorial_number .. (adjusted_primorial_factor_max/WORD_SIZE) {
+						let mut i = 0;
+						while i < sieve_words_used {
+							i = next_word_with_candidates(&sieve.factors_eliminated[0 .. sieve_words_used], i); // Skip the words where all the factors were eliminated
+							if i >= sieve_words_used {break;}
 							let mut sieve_word = !sieve.factors_eliminated[i];
 							while sieve_word != 0 {
 								let n_eliminated_until_next = sieve_word.trailing_zeros() as usize;
 								let candidate_factor = WORD_SIZE*i + n_eliminated_until_next;
-								factors_candidates.push(candidate_factor);
 								sieve_word &= sieve_word - 1; // Change the candidate's bit from 1 to 0.
+								if job.factor_stride.is_some_and(|(stride, offset)| (primorial_factor_start + candidate_factor) % stride != offset) {
+									continue; // Left to another instance
+								}
+								factors_candidates.push(candidate_factor);
+								if let Some((first_candidate_remainders, stride_remainders)) = &verification_remainders {
+									if candidates_extracted % params.verify_sieve_interval == 0 {
+										candidates_verified += 1;
+										if constellation_pattern.iter().any(|&offset| small_prime_factor(sieving_primes, first_candidate_remainders, stride_remainders, candidate_factor, offset).is_some()) {
+											sieve_verification_failures += 1;
+										}
+									}
+									candidates_extracted += 1;
+								}
 								// Make a Check Task once we have a batch of MAX_CANDIDATES_PER_CHECK_TASK Candidates
 								if factors_candidates.len() == MAX_CANDIDATES_PER_CHECK_TASK {
-									tasks.lock().unwrap().push_front(Task::new_check(task.job_id, primorial_factor_start, factors_candidates.clone()));
-									cv.notify_all();
-									stats.lock().unwrap().candidates_generated += MAX_CANDIDATES_PER_CHECK_TASK;
+									if let Some(job_windows) = job_windows.lock().unwrap().get_mut(&job.id) {
+										job_windows.add_check(primorial_factor_start);
+									}
+									params.check_task_insertion.insert(&mut tasks.lock().unwrap(), Task::new_check(task.job_id, primorial_factor_start, factors_candidates.clone()));
+									notify_workers(&cv, &params);
+									lock_stats(&stats, &mut stats_lock_wait).candidates_generated += MAX_CANDIDATES_PER_CHECK_TASK;
 									factors_candidates = vec![];
 								}
 							}
+							i += 1;
 						}
 						// Check Task for remaining Candidates
-						if factors_candidates.len() > 0 {
-							tasks.lock().unwrap().push_front(Task::new_check(task.job_id, primorial_factor_start, factors_candidates.clone()));
-							cv.notify_all();
-							stats.lock().unwrap().candidates_generated += factors_candidates.len();
+						if !factors_candidates.is_empty() {
+							if let Some(job_windows) = job_windows.lock().unwrap().get_mut(&job.id) {
+								job_windows.add_check(primorial_factor_start);
+							}
+							params.check_task_insertion.insert(&mut tasks.lock().unwrap(), Task::new_check(task.job_id, primorial_factor_start, factors_candidates.clone()));
+							notify_workers(&cv, &params);
+							lock_stats(&stats, &mut stats_lock_wait).candidates_generated += factors_candidates.len();
+						}
+						sieve.factors_eliminated[0 .. sieve_words_used].fill(0); // Only clear the words that could have been written by the elimination
+						if pooled_sieves {
+							sieve_pool.lock().unwrap().push(sieve);
+							sieve_pool_cv.notify_one();
+						}
+						else {
+							own_sieve = Some(sieve);
+						}
+						{
+							let mut stats = lock_stats(&stats, &mut stats_lock_wait);
+							let task_duration = time_since(timer_instant);
+							stats.sieving_duration += task_duration;
+							stats.sieve_task_durations.add(task_duration);
+							stats.candidates_verified += candidates_verified;
+							stats.sieve_verification_failures += sieve_verification_failures;
+							if params.profile_eliminations {
+								stats.elimination_counts = merged_counts(&stats.elimination_counts, &elimination_counts);
+							}
+						}
+						if let Some(job_stats) = job_stats.lock().unwrap().get_mut(&job.id) {
+							job_stats.primorial_factors_sieved += adjusted_primorial_factor_max;
+						}
+						if let Some(job_windows) = job_windows.lock().unwrap().get_mut(&job.id) {
+							job_windows.finish_sieve(primorial_factor_start);
 						}
-						sieve.factors_eliminated = vec![0 ; sieve_words];
-						stats.lock().unwrap().sieving_duration += time_since(timer_instant);
 					}
 					else if task.t == TaskType::Check {
 						timer_instant = Instant::now();
-						// Check whether the candidates first_candidate + f × primorial are indeed prime constellations
-						let target = job.target_min.clone();
+						// Check whether the candidates first_candidate + f × stride are indeed prime constellations
 						let primorial_factor_start = task.primorial_factor_start;
-						let first_candidate = target.clone() + primorial.clone() - (target.clone() % primorial.clone()) + primorial_offset + primorial_factor_start*primorial.clone();
+						let (candidates_base, stride) = candidate_lattice(job, &primorial, primorial_offset, params.exact_range_start).unwrap();
+						let first_candidate = candidates_base + primorial_factor_start*stride.clone();
+						// The checks are done in tiers: trial divisions by small primes, then a Fermat or Euler test for the numbers without small factors, and optionally a stronger confirmation for the tuples to be outputted
+						// The remainders of the candidates modulo small primes can be deduced from these ones without big integer operations
+						let trial_division_start = std::cmp::min(params.primorial_number - 1, primes.len());
+						let trial_division_primes = &primes[trial_division_start .. std::cmp::min(trial_division_start + params.trial_division_prime_count, primes.len())];
+						let first_candidate_remainders: Vec<usize> = trial_division_primes.iter().map(|&p| first_candidate.mod_u(p as u32) as usize).collect();
+						let stride_remainders: Vec<usize> = trial_division_primes.iter().map(|&p| stride.mod_u(p as u32) as usize).collect();
+						let native_arithmetic = job.target_max.significant_bits() <= 64; // Use native integers for the numbers fitting in 64 bits, much faster for small targets
+						let mut task_job_stats = JobStats::new(job.pattern.len());
+						let (mut trial_division_rejections, mut fermat_tests, mut confirmation_tests, mut confirmation_failures) = (0usize, 0usize, 0usize, 0usize);
+						let mut disagreements = vec![];
+						let check_positions = params.check_order.positions(job.pattern.len());
+						// Stop testing a Candidate once it cannot reach this number of primes anymore
+						let k_needed = match job.partial_output_threshold {
+							0 => job.k_min,
+							partial_output_threshold => std::cmp::min(partial_output_threshold, job.k_min)
+						};
 						for i in 0 .. task.factors_candidates.len() {
-							stats.lock().unwrap().tuple_counts[0] += 1;
+							let primorial_factor = primorial_factor_start + task.factors_candidates[i];
+							if job.dedup_outputs && reported_factors.lock().unwrap().get(&job.id).is_some_and(|factors| factors.contains(&primorial_factor)) {
+								continue; // Already reported
+							}
+							lock_stats(&stats, &mut stats_lock_wait).tuple_counts[0] += 1;
+							task_job_stats.tuple_counts[0] += 1;
 							let mut k = 0;
-							let candidate = first_candidate.clone() + &Integer::from(task.factors_candidates[i])*primorial.clone();
-							let mut output_pattern = vec![];
-							for f in 0 .. job.pattern.len() {
-								if is_prime_fermat(&(candidate.clone() + job.pattern[f])) {
-									k += 1;
-									output_pattern.push(job.pattern[f]);
-									stats.lock().unwrap().tuple_counts[k] += 1;
-								}
-								else if !job.pattern_min[f] {
-									if k + job.pattern.len() - f < job.k_min {
+							let mut rejected = false; // Set if a number required by pattern_min is not prime, whatever the other ones are
+							let candidate = first_candidate.clone() + &Integer::from(task.factors_candidates[i])*stride.clone();
+							let mut is_prime_at = vec![false; job.pattern.len()];
+							if !job.skip_primality_test {
+								for (j, &f) in check_positions.iter().enumerate() {
+									let number = candidate.clone() + job.pattern[f];
+									let is_prime = match small_prime_factor(trial_division_primes, &first_candidate_remainders, &stride_remainders, task.factors_candidates[i], job.pattern[f]) {
+										Some(p) => { // Tier 1, the number is divisible by a small prime p, so it is only prime if it is p itself
+											trial_division_rejections += (number != p) as usize;
+											number == p
+										}
+										None => { // Tier 2
+											fermat_tests += 1;
+											match number.to_u64().filter(|_| native_arithmetic) {
+												Some(n) => params.primality_test.is_probable_prime_u64(n),
+												None => params.primality_test.is_probable_prime(&number)
+											}
+										}
+									};
+									if params.cross_check && is_prime != (number.is_probably_prime(CROSS_CHECK_REPS) != rug::integer::IsPrime::No) {
+										disagreements.push(number.clone());
+									}
+									if is_prime {
+										k += 1;
+										is_prime_at[f] = true;
+										lock_stats(&stats, &mut stats_lock_wait).tuple_counts[k] += 1;
+										lock_stats(&stats, &mut stats_lock_wait).position_prime_counts[f] += 1;
+										task_job_stats.tuple_counts[k] += 1;
+										task_job_stats.position_prime_counts[f] += 1;
+									}
+									else if !job.pattern_min[f] {
+										if k + job.pattern.len() - j < k_needed {
+											break;
+										}
+									}
+									else {
+										rejected = true;
 										break;
 									}
 								}
-								else {
-									break;
+							}
+							if params.confirmation_reps > 0 && k >= job.k_min && !rejected && !job.skip_primality_test { // Tier 3
+								for (f, is_prime) in is_prime_at.iter_mut().enumerate() {
+									if *is_prime {
+										confirmation_tests += 1;
+										if Integer::from(&candidate + job.pattern[f]).is_probably_prime(params.confirmation_reps as u32) == rug::integer::IsPrime::No {
+											confirmation_failures += 1;
+											*is_prime = false;
+											k = k.saturating_sub(1);
+											rejected |= job.pattern_min[f];
+										}
+									}
+								}
+							}
+							if (k >= job.k_min && !rejected) || job.skip_primality_test {
+								// All the conditions are checked before updating any of their states, so an output dropped by one of them is not seen as reported or emitted by the others. The states stay locked until the output is pushed, so two workers cannot both pass the checks
+								let mut reported_factors = reported_factors.lock().unwrap();
+								let mut last_output_ns = last_output_ns.lock().unwrap();
+								let mut dedup_filter = dedup_filter.lock().unwrap();
+								let new_factor = !job.dedup_outputs || !reported_factors.get(&job.id).is_some_and(|factors| factors.contains(&primorial_factor));
+								if new_factor && spaced_output(&last_output_ns, job, &candidate) && unreported_output(&dedup_filter, &candidate) {
+									if job.dedup_outputs {
+										reported_factors.entry(job.id).or_default().insert(primorial_factor);
+									}
+									if job.min_output_spacing != 0 {
+										last_output_ns.insert(job.id, candidate.clone());
+									}
+									if let Some(filter) = dedup_filter.as_mut() {
+										filter.insert(&candidate);
+									}
+									let output_pattern: Vec<isize> = match job.skip_primality_test {
+										true => job.pattern.clone(),
+										false => (0 .. job.pattern.len()).filter(|&f| is_prime_at[f]).map(|f| job.pattern[f]).collect()
+									};
+									let mut output = output.lock().unwrap();
+									output.push_front(Output{
+										n: candidate.clone(),
+										pattern: output_pattern,
+										job_id: job.id,
+										worker_id: worker_id,
+										verified: !job.skip_primality_test,
+										seq: output_seq.fetch_add(1, Ordering::SeqCst)
+									})
 								}
 							}
-							if k >= job.k_min {
-								output.lock().unwrap().push_front(Output{
+							else if job.partial_output_threshold > 0 && k >= job.partial_output_threshold && k < job.k_min && !rejected && !job.skip_primality_test {
+								let mut partial_output = partial_output.lock().unwrap();
+								partial_output.push_front(Output{
 									n: candidate.clone(),
-									pattern: output_pattern.clone(),
+									pattern: (0 .. job.pattern.len()).filter(|&f| is_prime_at[f]).map(|f| job.pattern[f]).collect(),
 									job_id: job.id,
-									worker_id: worker_id
+									worker_id: worker_id,
+									verified: true,
+									seq: output_seq.fetch_add(1, Ordering::SeqCst)
 								})
 							}
 						}
-						stats.lock().unwrap().testing_duration += time_since(timer_instant);
-						stats.lock().unwrap().candidates_tested += task.factors_candidates.len();
+						let task_duration = time_since(timer_instant);
+						lock_stats(&stats, &mut stats_lock_wait).testing_duration += task_duration;
+						lock_stats(&stats, &mut stats_lock_wait).check_task_durations.add(task_duration);
+						lock_stats(&stats, &mut stats_lock_wait).candidates_tested += task.factors_candidates.len();
+						{
+							let mut stats = lock_stats(&stats, &mut stats_lock_wait);
+							stats.trial_division_rejections += trial_division_rejections;
+							stats.fermat_tests += fermat_tests;
+							stats.confirmation_tests += confirmation_tests;
+							stats.confirmation_failures += confirmation_failures;
+							stats.disagreements.append(&mut disagreements);
+						}
+						task_job_stats.candidates_tested = task.factors_candidates.len();
+						if let Some(job_stats) = job_stats.lock().unwrap().get_mut(&job.id) {
+							job_stats.add(&task_job_stats);
+						}
+						if let Some(job_windows) = job_windows.lock().unwrap().get_mut(&job.id) {
+							job_windows.finish_check(primorial_factor_start);
+						}
 					}
+					if let Some(worker_stats) = worker_stats.lock().unwrap().get_mut(worker_id) {
+						worker_stats.tasks_processed += 1;
+						worker_stats.busy_duration += time_since(task_start_instant);
+						worker_stats.stats_lock_wait_duration += stats_lock_wait;
+						if task.t == TaskType::Check {
+							worker_stats.candidates_tested += task.factors_candidates.len();
+						}
+					}
+					finish_task(&busy_workers, &idle_cv);
 				}
 			});
+			if let Ok(worker_thread) = worker_thread {
+				self.worker_threads.push(worker_thread);
+			}
 		}
 	}
 	
-	pub fn add_job(&mut self, job: Job) -> (Vec<String>, Vec<String>) {
-		let (mut warnings, mut errors) = (vec![], vec![]);
-		if self.jobs.lock().unwrap().contains_key(&job.id) {
-			errors.push(format!("A Job {} was already added to the Stella instance.", job.id).to_string());
-		}
-		if job.pattern.len() != job.pattern_min.len() {
-			errors.push(format!("The target pattern {:?} and minimum pattern {:?} Vecs must have the same size.", job.pattern, job.pattern_min).to_string());
+	// Makes the workers exit once they are done with their current Task and waits for them, the queued Tasks are kept. The threads of on_stats_interval and of the heartbeat are stopped too
+	pub fn stop_workers(&mut self) -> () {
+		self.join_workers();
+		for (stop, stats_thread) in self.stats_threads.drain(..) {
+			stop.store(true, Ordering::SeqCst);
+			stats_thread.thread().unpark();
+			let _ = stats_thread.join();
 		}
-		if job.k_min > job.pattern.len() {
-			errors.push(format!("The minimum tuple length {} must not exceed the constellation pattern length {}.", job.k_min, job.pattern.len()).to_string());
+	}
+	
+	// Only stops the worker threads, the counterpart of spawn_workers
+	fn join_workers(&mut self) -> () {
+		{
+			let _tasks = self.tasks.lock().unwrap();
+			self.stopping.store(true, Ordering::SeqCst);
+			self.cv.notify_all();
 		}
-		if job.target_max < job.target_min {
-			errors.push("The target upper bound must be higher than the target lower bound.".to_string());
+		for worker_thread in self.worker_threads.drain(..) {
+			let _ = worker_thread.join();
+		}
+	}
+	
+	// Spawns a thread calling the callback with a snapshot of the Stats every interval, until stop_workers is called or the instance is dropped
+	// Returns an error without spawning anything if the interval is zero
+	pub fn on_stats_interval(&mut self, interval: Duration, callback: Arc<dyn Fn(Stats) + Send + Sync>) -> Result<(), String> {
+		if interval.is_zero() {
+			return Err("The stats interval must not be zero.".to_string());
+		}
+		let stop = Arc::new(AtomicBool::new(false));
+		let thread_stop = stop.clone();
+		let stats = self.stats.clone();
+		let stats_thread = thread::Builder::new().name("Stats".to_string()).spawn(move || {
+			let mut next_instant = Instant::now() + interval;
+			while !thread_stop.load(Ordering::SeqCst) {
+				let now = Instant::now();
+				if now >= next_instant {
+					let snapshot = stats.lock().unwrap().clone();
+					callback(snapshot);
+					next_instant += interval;
+				}
+				else {
+					thread::park_timeout(next_instant - now); // Unparked by stop_workers
+				}
+			}
+		}).unwrap();
+		self.stats_threads.push((stop, stats_thread));
+		return Ok(());
+	}
+	
+	// Runs a tiny search of twin primes and checks it against a brute force enumeration, to validate the sieve and the checks, for example after changes in the code
+	// The Candidates generated by the sieve must be exactly the numbers of the range without factors among the sieving primes, and the outputs exactly the ones of them that are twin primes, confirmed with GMP
+	pub fn self_test() -> Result<(), String> {
+		let pattern = vec![0, 2];
+		let mut stella = Stella::new();
+		stella.set_params(Params {
+			workers: 2,
+			constellation_pattern: pattern.clone(),
+			prime_table_limit: 2000,
+			primorial_number: 5,
+			sieve_size: 1024, // Several windows, the last one being partial
+			..Default::default()
+		});
+		stella.init().map_err(|error| error.to_string())?;
+		stella.start_workers();
+		let primorial = stella.primorial();
+		let target_min = Integer::from(1000000000039u64);
+		let primorial_factor_max: usize = 4321;
+		let target_max = target_min.clone() + primorial.clone()*primorial_factor_max;
+		let base = target_min.clone() + &primorial - Integer::from(&target_min % &primorial) + stella.params.primorial_offset;
+		let sieving_primes: Vec<usize> = stella.primes.iter().filter(|&&p| !primorial.is_divisible_u(p as u32)).cloned().collect(); // All the primes of the table not in the primorial
+		let mut expected_candidates = vec![];
+		for f in 0 .. primorial_factor_max {
+			let n = base.clone() + primorial.clone()*f;
+			if pattern.iter().all(|&offset| sieving_primes.iter().all(|&p| Integer::from(&n + offset) == p || !Integer::from(&n + offset).is_divisible_u(p as u32))) {
+				expected_candidates.push(n);
+			}
+		}
+		let expected_outputs: Vec<Integer> = expected_candidates.iter().filter(|&n| verify_constellation_strong(n, &pattern, 30)).cloned().collect();
+		let mut result = Ok(());
+		for (id, skip_primality_test) in [(1, true), (2, false)] {
+			stella.add_job(Job {
+				id: id,
+				clear_previous_jobs: true,
+				pattern: pattern.clone(),
+				target_min: target_min.clone(),
+				target_max: target_max.clone(),
+				k_min: pattern.len(),
+				pattern_min: vec![true; pattern.len()],
+				dedup_outputs: false,
+				alignment: None,
+				skip_primality_test: skip_primality_test,
+				partial_output_threshold: 0,
+				min_output_spacing: Integer::from(0),
+				factor_stride: None
+			});
+			if !stella.wait_idle(Duration::from_secs(60)) {
+				result = Err("The self test search did not finish in time.".to_string());
+				break;
+			}
+			let outputs: Vec<Integer> = stella.drain_outputs_sorted().into_iter().map(|output| output.n).collect();
+			let expected = if skip_primality_test {&expected_candidates} else {&expected_outputs};
+			if outputs != *expected {
+				let missing = expected.iter().filter(|&n| !outputs.contains(n)).count();
+				let extra = outputs.iter().filter(|&n| !expected.contains(n)).count();
+				result = Err(format!("The {} do not match the brute force enumeration ({} expected, {} found, {} missing, {} extra).", if skip_primality_test {"Candidates"} else {"outputs"}, expected.len(), outputs.len(), missing, extra).to_string());
+				break;
+			}
+		}
+		stella.stop_workers();
+		return result;
+	}
+	
+	// Like add_job, but returns a handle to follow the Job and the warnings, or the errors
+	pub fn add_job_tracked(&mut self, job: Job) -> Result<(JobHandle, Vec<String>), Vec<String>> {
+		let job_id = job.id;
+		let (warnings, errors) = self.add_job(job);
+		if !errors.is_empty() {
+			return Err(errors);
+		}
+		let handle = JobHandle {
+			job_id: job_id,
+			jobs: self.jobs.clone(),
+			tasks: self.tasks.clone(),
+			idle_cv: self.idle_cv.clone(),
+			job_stats: self.job_stats.clone(),
+			job_windows: self.job_windows.clone(),
+			output: self.output.clone(),
+			outputs_held: self.outputs_held.clone(),
+			last_output_ns: self.last_output_ns.clone()
+		};
+		return Ok((handle, warnings));
+	}
+	
+	pub fn add_job(&mut self, job: Job) -> (Vec<String>, Vec<String>) {
+		return self.add_job_from(job, 0);
+	}
+	
+	// Adds the Job with the primorial factors below the start considered done, to resume a search
+	fn add_job_from(&mut self, job: Job, primorial_factor_start: usize) -> (Vec<String>, Vec<String>) {
+		let (mut warnings, mut errors) = (vec![], vec![]);
+		if self.jobs.lock().unwrap().contains_key(&job.id) {
+			errors.push(format!("A Job {} was already added to the Stella instance.", job.id).to_string());
+		}
+		errors.extend(job.validate());
+		// The wheel given by the Primorial Offset only makes sense for tuples spanning less than one primorial
+		if let Some(&offset) = job.pattern.iter().max_by_key(|&&offset| offset.unsigned_abs()) {
+			if self.primorial <= offset.unsigned_abs() {
+				errors.push(format!("The offset {} of the target pattern is not smaller than the primorial {}, the pattern is too wide for the wheel. Check the pattern or increase the Primorial Number.", offset, self.primorial).to_string());
+			}
+		}
+		if let Some(p) = inadmissible_prime(&job.pattern) {
+			warnings.push(format!("The target pattern {:?} is not admissible (one of the numbers is always divisible by {}), full tuples can only be found for tiny targets.", job.pattern, p).to_string());
+		}
+		if !job.clear_previous_jobs { // Overlapping Jobs with the same pattern would test the same Candidates twice
+			for (id, other_job) in self.jobs.lock().unwrap().iter() {
+				if *id != job.id && other_job.pattern == job.pattern && other_job.target_min <= job.target_max && job.target_min <= other_job.target_max {
+					warnings.push(format!("The target range overlaps with the one of the Job {} with the same pattern, the Candidates in the overlap will be tested twice.", id).to_string());
+				}
+			}
+		}
+		if job.target_max < job.target_min { // The error was given by validate
 			return (warnings, errors);
 		}
-		let primorial = self.primorial.clone();
-		let primorial_factor_max = match ((job.target_max.clone() - job.target_min.clone())/primorial.clone()).to_usize() {
+		let stride = match candidate_lattice(&job, &self.primorial, self.params.primorial_offset, self.params.exact_range_start) {
+			Ok((candidates_base, stride)) => {
+				if job.alignment.is_some() {
+					// If a sieving prime divides the stride, the numbers at some offset are either never or always divisible by it
+					for &p in self.primes.get(first_sieving_prime_index(&self.params) ..).unwrap_or(&[]) {
+						if (stride.clone() % p) == 0 {
+							if let Some(offset) = self.params.constellation_pattern.iter().find(|&&offset| (candidates_base.clone() + offset) % p == 0) {
+								errors.push(format!("With this alignment, the numbers at the offset {} of the Candidates would always be divisible by {}.", offset, p).to_string());
+								return (warnings, errors);
+							}
+						}
+					}
+				}
+				stride
+			}
+			Err(error) => {
+				errors.push(error);
+				return (warnings, errors);
+			}
+		};
+		let mut bounded = true;
+		let mut primorial_factor_max = match ((job.target_max.clone() - job.target_min.clone())/stride.clone()).to_usize() {
 			Some(primorial_factor_max) => primorial_factor_max,
 			_ => {
 				warnings.push(format!("The primorial factor limit exceeds usize::MAX = {}, the search will stop before the target max. Consider increasing the Primorial Number.", usize::MAX).to_string());
+				bounded = false;
 				usize::MAX
 			}
 		};
-		if primorial_factor_max == 0 {
-			errors.push("The Primorial Number is too big.".to_string());
+		let point_search = job.target_max == job.target_min; // Only test the first Candidate at or above the target
+		if self.params.single_sieve_window && !point_search { // Cut the range after the first window, so the Job still completes
+			let single_window_end = primorial_factor_start.saturating_add(sieve_window_size(&self.params));
+			if single_window_end < primorial_factor_max {
+				primorial_factor_max = single_window_end;
+				bounded = true;
+			}
+		}
+		let point_candidate_checked = job.factor_stride.is_none_or(|(_, offset)| offset == 0); // The only Candidate of a point search has the primorial factor 0
+		if point_search && !point_candidate_checked {
+			warnings.push("The only Candidate of this point search has the primorial factor 0, which is not in the share of the factor stride, so nothing will be checked.".to_string());
 		}
-		if errors.len() == 0 {
+		if primorial_factor_max == 0 && !point_search {
+			match job.alignment {
+				None => errors.push("The target range is smaller than the primorial, the Primorial Number is too big for this Job.".to_string()),
+				Some(_) => errors.push("The target range is smaller than the Candidates stride, the Primorial Number or alignment is too big for this Job.".to_string())
+			}
+		}
+		if errors.is_empty() {
 			if job.clear_previous_jobs {
 				self.jobs.lock().unwrap().clear();
+				self.reported_factors.lock().unwrap().clear();
+				self.last_output_ns.lock().unwrap().clear();
+				self.tasks.lock().unwrap().clear(); // All the queued Tasks belong to the cleared Jobs, so the workers do not have to pop and skip them
+				self.idle_cv.notify_all();
 			}
 			self.jobs.lock().unwrap().insert(job.id, job.clone());
-			self.tasks.lock().unwrap().push_back(Task::new_sieve(job.id, 0, primorial_factor_max));
-			self.cv.notify_all();
+			let mut new_job_stats = JobStats::new(job.pattern.len());
+			new_job_stats.primorial_factor_max = if point_search {0} else {primorial_factor_max};
+			new_job_stats.bounded = bounded;
+			if !point_search {
+				new_job_stats.primorial_factor_start = primorial_factor_start;
+				new_job_stats.primorial_factors_sieved = primorial_factor_start;
+			}
+			self.job_stats.lock().unwrap().insert(job.id, new_job_stats);
+			let mut new_job_windows = JobWindows::new();
+			if !point_search {
+				new_job_windows.completed_until = primorial_factor_start;
+			}
+			if point_search { // Single window containing the only Candidate
+				new_job_windows.start_sieve(0, 1);
+				if point_candidate_checked {
+					new_job_windows.add_check(0);
+				}
+				new_job_windows.finish_sieve(0);
+			}
+			self.job_windows.lock().unwrap().insert(job.id, new_job_windows);
+			if point_search && point_candidate_checked {
+				self.tasks.lock().unwrap().push_back(Task::new_check(job.id, 0, vec![0]));
+				self.stats.lock().unwrap().candidates_generated += 1;
+			}
+			else if !point_search && primorial_factor_start < primorial_factor_max {
+				self.tasks.lock().unwrap().push_back(Task::new_sieve(job.id, primorial_factor_start, primorial_factor_max));
+			}
+			notify_workers(&self.cv, &self.params);
 		}
 		return (warnings, errors);
 	}
 	
+	// Rough estimate of how many Candidates the sieve will generate for the given Job, the init method must have been called before
+	pub fn total_candidates_in_range(&self, job: &Job) -> f64 {
+		let primorial_factors = (Integer::from(&job.target_max - &job.target_min)/self.candidate_stride(job)).to_f64();
+		let strided_fraction = job.factor_stride.map_or(1f64, |(stride, _)| 1f64/(stride as f64));
+		return primorial_factors*strided_fraction*self.expected_survival_fraction();
+	}
+	
+	// Difference between two consecutive potential Candidates of the Job: the primorial, multiplied by alignment/gcd(alignment, primorial) if the Job has an alignment
+	// The primorial is returned for an invalid alignment
+	pub fn candidate_stride(&self, job: &Job) -> Integer {
+		return candidate_lattice(job, &self.primorial, self.params.primorial_offset, self.params.exact_range_start).map_or(self.primorial.clone(), |(_, stride)| stride);
+	}
+	
+	// First and last potential Candidates that the Job would test, which differ from target_min and target_max because of the primorial offset, alignment and factor stride, the init method must have been called before
+	// They are the ones of the primorial factors 0 and primorial_factor_max - 1 (or the first and last ones of the factor stride), or the only Candidate for a point search. Like for candidate_stride, an invalid alignment is ignored
+	pub fn candidate_range(&self, job: &Job) -> (Integer, Integer) {
+		let (candidates_base, stride) = candidate_lattice(job, &self.primorial, self.params.primorial_offset, self.params.exact_range_start)
+			.unwrap_or_else(|_| candidate_lattice(&Job {alignment: None, ..job.clone()}, &self.primorial, self.params.primorial_offset, self.params.exact_range_start).unwrap());
+		if job.target_max <= job.target_min { // Point search
+			return (candidates_base.clone(), candidates_base);
+		}
+		let primorial_factor_max = ((job.target_max.clone() - job.target_min.clone())/stride.clone()).to_usize().unwrap_or(usize::MAX); // Clamped like in add_job
+		let (first_factor, last_factor) = match job.factor_stride.filter(|_| primorial_factor_max > 0) {
+			Some((factor_stride, offset)) => (offset, (primorial_factor_max - 1).saturating_sub(((primorial_factor_max - 1) % factor_stride + factor_stride - offset) % factor_stride).max(offset)), // The first one if there is a single Candidate or none
+			None => (0, primorial_factor_max.saturating_sub(1))
+		};
+		return (candidates_base.clone() + stride.clone()*first_factor, candidates_base + stride*last_factor);
+	}
+	
+	// Inverse of the Candidate generation: finds the primorial factor f and the offset o of the Job pattern such that n = first_candidate + f × stride + o, the init method must have been called before
+	// None if n is not a number of a tuple of the Job's Candidate lattice (the range is not checked)
+	pub fn decompose(&self, n: &Integer, job: &Job) -> Option<(usize, Integer)> {
+		let (candidates_base, stride) = candidate_lattice(job, &self.primorial, self.params.primorial_offset, self.params.exact_range_start).ok()?;
+		for &offset in job.pattern.iter() {
+			let difference = Integer::from(n - offset) - &candidates_base;
+			if difference >= 0 && difference.is_divisible(&stride) {
+				if let Some(primorial_factor) = (difference/&stride).to_usize() {
+					return Some((primorial_factor, Integer::from(offset)));
+				}
+			}
+		}
+		return None;
+	}
+	
+	// Theoretical fraction of the primorial factors surviving the sieve, the init method must have been called before
+	// Each sieving prime p eliminates the factors for which one of the k numbers of the pattern is a multiple of p, so k/p of them if the pattern offsets are distinct modulo p (which is the case for p greater than the pattern width), and these eliminations are assumed independent for different primes
+	pub fn expected_survival_fraction(&self) -> f64 {
+		let pattern_length = self.params.constellation_pattern.len() as f64;
+		let mut fraction = 1f64;
+		for i in first_sieving_prime_index(&self.params) .. self.primes.len() {
+			fraction *= (1f64 - pattern_length/(self.primes[i] as f64)).max(0f64);
+		}
+		return fraction;
+	}
+	
+	// Hardy-Littlewood constant of the pattern using the whole prime table, the init method must have been called before
+	pub fn hardy_littlewood_constant(&self, pattern: &[isize]) -> f64 {
+		return hardy_littlewood_constant(pattern, &self.primes);
+	}
+	
+	// Blocks until no Task is queued and no worker is processing one, or until the timeout. Returns whether the idle state was reached.
+	pub fn wait_idle(&self, timeout: Duration) -> bool {
+		let deadline = Instant::now() + timeout;
+		loop {
+			let tasks = self.tasks.lock().unwrap();
+			let busy_workers = self.busy_workers.lock().unwrap();
+			if tasks.is_empty() && *busy_workers == 0 {
+				return true;
+			}
+			drop(tasks);
+			let now = Instant::now();
+			if now >= deadline {
+				return false;
+			}
+			let _ = self.idle_cv.wait_timeout(busy_workers, deadline - now).unwrap();
+		}
+	}
+	
+	// Blocking convenience returning the longest tuple found for the Job (the first found among the longest ones), when its range is exhausted or after the timeout
+	// The init method must have been called before, and the workers must not be running: they are started for the search, then stopped, and the Job is removed at the end. None is returned if the workers were already running, if the Job was rejected or if nothing was found
+	// The threads of on_stats_interval and of the heartbeat are left running
+	pub fn find_best(&mut self, job: Job, timeout: Duration) -> Option<Output> {
+		if !self.worker_threads.is_empty() {
+			return None;
+		}
+		let deadline = Instant::now() + timeout;
+		let job_id = job.id;
+		self.spawn_workers();
+		let (_, errors) = self.add_job(job);
+		let mut best: Option<Output> = None;
+		if errors.is_empty() {
+			loop {
+				let now = Instant::now();
+				let idle = now >= deadline || self.wait_idle(std::cmp::min(deadline - now, Duration::from_millis(100)));
+				// Only the outputs of this Job are consumed, the ones of other Jobs stay in the queue
+				self.output.lock().unwrap().retain(|output| {
+					if output.job_id != job_id {
+						return true;
+					}
+					if best.as_ref().is_none_or(|best| output.pattern.len() > best.pattern.len() || (output.pattern.len() == best.pattern.len() && output.seq < best.seq)) {
+						best = Some(output.clone());
+					}
+					return false;
+				});
+				if idle || Instant::now() >= deadline {
+					break;
+				}
+			}
+		}
+		self.join_workers();
+		self.discard_job(job_id);
+		return best;
+	}
+	
+	// Tests sample Candidates of the Job at every position, without stopping at the composite numbers, and returns the fraction of primes at each position of the Job pattern
+	// This can be given to CheckOrder::Probabilities to test first the positions the most likely to be composite. The Job is otherwise searched normally, so this takes the time of sieving and fully testing the sample
+	// Like find_best, the workers are started and stopped (an empty Vec is returned if they were already running), the outputs of other Jobs are kept and the calibration Candidates are counted in the stats
+	// The calibration Job gets an unused id, so a Job with the same id is not replaced
+	pub fn calibrate(&mut self, job: &Job, sample: usize) -> Vec<f64> {
+		if !self.worker_threads.is_empty() {
+			return vec![];
+		}
+		let mut prime_counts = vec![0usize ; job.pattern.len()];
+		let mut tested = 0usize;
+		let calibration_job_id = {
+			let jobs = self.jobs.lock().unwrap();
+			(0 ..= usize::MAX).rev().find(|id| !jobs.contains_key(id)).unwrap()
+		};
+		let calibration_job = Job {
+			id: calibration_job_id,
+			clear_previous_jobs: false,
+			k_min: 0, // Output every Candidate with all its primes
+			pattern_min: vec![false ; job.pattern.len()],
+			dedup_outputs: false,
+			skip_primality_test: false,
+			partial_output_threshold: 0,
+			min_output_spacing: Integer::from(0),
+			..job.clone()
+		};
+		let dedup_filter = self.dedup_filter.lock().unwrap().take(); // The calibration Candidates must not be seen as reported
+		self.spawn_workers();
+		let (_, errors) = self.add_job(calibration_job);
+		if errors.is_empty() {
+			while tested < sample {
+				let idle = self.wait_idle(Duration::from_millis(100));
+				self.output.lock().unwrap().retain(|output| {
+					if output.job_id != calibration_job_id {
+						return true;
+					}
+					if tested < sample {
+						tested += 1;
+						for (f, prime_count) in prime_counts.iter_mut().enumerate() {
+							*prime_count += output.pattern.contains(&job.pattern[f]) as usize;
+						}
+					}
+					return false;
+				});
+				if idle {
+					break;
+				}
+			}
+		}
+		self.join_workers(); // Waits for the workers, so no calibration output or filter insertion comes later
+		self.discard_job(calibration_job_id);
+		self.job_stats.lock().unwrap().remove(&calibration_job_id);
+		self.job_windows.lock().unwrap().remove(&calibration_job_id);
+		self.output.lock().unwrap().retain(|output| output.job_id != calibration_job_id);
+		*self.dedup_filter.lock().unwrap() = dedup_filter;
+		return prime_counts.iter().map(|&prime_count| (prime_count as f64)/(std::cmp::max(tested, 1) as f64)).collect();
+	}
+	
+	// Removes a Job and its queued Tasks, once the workers are stopped
+	fn discard_job(&mut self, job_id: usize) -> () {
+		self.jobs.lock().unwrap().remove(&job_id);
+		self.last_output_ns.lock().unwrap().remove(&job_id);
+		self.tasks.lock().unwrap().retain(|task| task.job_id != job_id);
+		self.idle_cv.notify_all();
+	}
+	
+	// Number of workers currently processing a Task (from its pop to the push of its Outputs), the others are waiting for Tasks
+	pub fn busy_workers(&self) -> usize {
+		return *self.busy_workers.lock().unwrap();
+	}
+	
+	// Number of Tasks waiting to be processed
+	pub fn queued_tasks(&self) -> usize {
+		return self.tasks.lock().unwrap().len();
+	}
+	
 	pub fn pop_output(&mut self) -> Option<Output> {
+		if self.outputs_held.load(Ordering::SeqCst) {
+			return None;
+		}
 		return self.output.lock().unwrap().pop_back();
 	}
 	
+	// Pops a tuple shorter than k_min from the separate queue of the Jobs using partial_output_threshold
+	pub fn pop_partial_output(&mut self) -> Option<Output> {
+		if self.outputs_held.load(Ordering::SeqCst) {
+			return None;
+		}
+		return self.partial_output.lock().unwrap().pop_back();
+	}
+	
+	// Pops all the Outputs, in the order in which they were found
+	pub fn drain_outputs(&mut self) -> Vec<Output> {
+		if self.outputs_held.load(Ordering::SeqCst) {
+			return vec![];
+		}
+		return self.output.lock().unwrap().drain(..).rev().collect();
+	}
+	
+	// Pops the oldest Event
+	pub fn pop_event(&mut self) -> Option<Event> {
+		return self.events.lock().unwrap().pop_back();
+	}
+	
+	// Number of Events dropped because they were not popped before the queue reached MAX_QUEUED_EVENTS
+	pub fn dropped_events(&self) -> usize {
+		return self.dropped_events.load(Ordering::SeqCst);
+	}
+	
+	// Outputs whose base number is in the filter are not reported, and the reported ones are inserted. None removes the filter
+	pub fn set_dedup_filter(&mut self, filter: Option<BloomFilter>) -> () {
+		*self.dedup_filter.lock().unwrap() = filter;
+	}
+	
+	// Copy of the current filter, to save it with to_bytes for the next run
+	pub fn dedup_filter(&self) -> Option<BloomFilter> {
+		return self.dedup_filter.lock().unwrap().clone();
+	}
+	
+	// Stops delivering the Outputs until release_outputs is called, the workers keep searching and queuing them
+	pub fn hold_outputs(&self) -> () {
+		self.outputs_held.store(true, Ordering::SeqCst);
+	}
+	
+	// The workers waiting because max_held_outputs was reached resume
+	pub fn release_outputs(&self) -> () {
+		let _tasks = self.tasks.lock().unwrap(); // So a worker cannot miss the notification between its check and its wait
+		self.outputs_held.store(false, Ordering::SeqCst);
+		self.cv.notify_all();
+	}
+	
+	// Pops all the Outputs, sorted by ascending base number
+	pub fn drain_outputs_sorted(&mut self) -> Vec<Output> {
+		let mut outputs = self.drain_outputs();
+		outputs.sort();
+		return outputs;
+	}
+	
 	pub fn stats(&self) -> Stats {
 		return self.stats.lock().unwrap().clone();
 	}
+	
+	// Statistics of each worker, indexed by worker Id
+	pub fn worker_stats(&self) -> Vec<WorkerStats> {
+		return self.worker_stats.lock().unwrap().clone();
+	}
+	
+	// Statistics of a single Job, still available after the Job was cleared
+	pub fn job_stats(&self, job_id: usize) -> Option<JobStats> {
+		return self.job_stats.lock().unwrap().get(&job_id).cloned();
+	}
+	
+	pub fn job_progress(&self, job_id: usize) -> Option<f64> {
+		return self.job_stats.lock().unwrap().get(&job_id).map(|job_stats| job_stats.progress());
+	}
+	
+	// None if the Job is unknown, unbounded, or did not progress yet
+	pub fn job_eta(&self, job_id: usize) -> Option<Duration> {
+		return self.job_stats.lock().unwrap().get(&job_id).and_then(|job_stats| job_stats.eta());
+	}
+	
+	// Which primorial factors of the Job were fully processed and which are in progress, None if no such Job was added
+	pub fn checkpoint(&self, job_id: usize) -> Option<Checkpoint> {
+		return self.job_windows.lock().unwrap().get(&job_id).map(|job_windows| job_windows.checkpoint(job_id));
+	}
+	
+	// Writes the state needed to resume the search with load_state: the not yet completed Jobs with their Checkpoints, the cumulative Stats and the output sequence number
+	// The state is written to a temporary file renamed afterwards, so an interruption while saving does not corrupt a previous save
+	pub fn save_state<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+		let mut state = vec![
+			format!("fingerprint={}", self.configuration_fingerprint()),
+			format!("output_seq={}", self.output_seq.load(Ordering::SeqCst))
+		];
+		let stats = self.stats();
+		let counts = |counts: &[usize]| counts.iter().map(|count| count.to_string()).collect::<Vec<String>>().join(",");
+		state.push(format!("elapsed={}", time_since(stats.search_start_instant)));
+		state.push(format!("stats.sieving_duration={}", stats.sieving_duration));
+		state.push(format!("stats.candidates_generated={}", stats.candidates_generated));
+		state.push(format!("stats.testing_duration={}", stats.testing_duration));
+		state.push(format!("stats.candidates_tested={}", stats.candidates_tested));
+		state.push(format!("stats.tuple_counts={}", counts(&stats.tuple_counts)));
+		state.push(format!("stats.position_prime_counts={}", counts(&stats.position_prime_counts)));
+		state.push(format!("stats.candidates_verified={}", stats.candidates_verified));
+		state.push(format!("stats.sieve_verification_failures={}", stats.sieve_verification_failures));
+		state.push(format!("stats.stale_tasks_discarded={}", stats.stale_tasks_discarded));
+		state.push(format!("stats.trial_division_rejections={}", stats.trial_division_rejections));
+		state.push(format!("stats.fermat_tests={}", stats.fermat_tests));
+		state.push(format!("stats.confirmation_tests={}", stats.confirmation_tests));
+		state.push(format!("stats.confirmation_failures={}", stats.confirmation_failures));
+		state.push(format!("stats.elimination_counts={}", counts(&stats.elimination_counts)));
+		state.push(format!("stats.disagreements={}", stats.disagreements.iter().map(|n| n.to_string()).collect::<Vec<String>>().join(",")));
+		let mut jobs: Vec<Job> = self.jobs.lock().unwrap().values().cloned().collect();
+		jobs.sort_by_key(|job| job.id);
+		for job in jobs {
+			let completed_until = match self.checkpoint(job.id) {
+				Some(checkpoint) => checkpoint.completed_until,
+				None => continue
+			};
+			let primorial_factor_max = self.job_stats.lock().unwrap().get(&job.id).map_or(0, |job_stats| job_stats.primorial_factor_max);
+			if completed_until < std::cmp::max(primorial_factor_max, 1) { // The completed Jobs are not resumed
+				state.push(format!("job={}", job_state(&job, completed_until)));
+			}
+		}
+		let temporary_path = path.as_ref().with_extension("tmp");
+		fs::write(&temporary_path, state.join("\n") + "\n").map_err(|error| format!("Could not write the state to {}: {}.", temporary_path.display(), error))?;
+		return fs::rename(&temporary_path, path.as_ref()).map_err(|error| format!("Could not move the state to {}: {}.", path.as_ref().display(), error));
+	}
+	
+	// Creates and initializes an instance with the given parameters, then restores a state written by save_state. The Jobs are added back and resume from their Checkpoints once the workers are started, which also restores the Stats
+	// The parameters must give the same configuration fingerprint as the ones of the saved instance. Returns the instance and the warnings of set_params and add_job
+	pub fn load_state<P: AsRef<Path>>(path: P, params: Params) -> Result<(Stella, Vec<String>), String> {
+		let state = fs::read_to_string(path.as_ref()).map_err(|error| format!("Could not read the state from {}: {}.", path.as_ref().display(), error))?;
+		let mut stella = Stella::new();
+		let mut warnings = stella.set_params(params);
+		stella.init().map_err(|error| error.to_string())?;
+		let (mut fingerprint, mut output_seq, mut elapsed) = (None, 0u64, 0f64);
+		let mut stats = Stats::new();
+		let mut jobs = vec![];
+		for line in state.lines().filter(|line| !line.trim().is_empty()) {
+			let (key, value) = line.trim().split_once('=').ok_or(format!("Invalid state line {}.", line.trim()))?;
+			let invalid_value = || format!("Invalid state value {} for {}.", value, key);
+			let float = || value.parse::<f64>().map_err(|_| invalid_value());
+			let count = || value.parse::<usize>().map_err(|_| invalid_value());
+			let counts = || value.split(',').filter(|count| !count.is_empty()).map(|count| count.parse::<usize>()).collect::<Result<Vec<usize>, _>>().map_err(|_| invalid_value());
+			match key {
+				"fingerprint" => {fingerprint = Some(value.to_string());}
+				"output_seq" => {output_seq = value.parse::<u64>().map_err(|_| invalid_value())?;}
+				"elapsed" => {elapsed = float()?;}
+				"stats.sieving_duration" => {stats.sieving_duration = float()?;}
+				"stats.candidates_generated" => {stats.candidates_generated = count()?;}
+				"stats.testing_duration" => {stats.testing_duration = float()?;}
+				"stats.candidates_tested" => {stats.candidates_tested = count()?;}
+				"stats.tuple_counts" => {stats.tuple_counts = counts()?;}
+				"stats.position_prime_counts" => {stats.position_prime_counts = counts()?;}
+				"stats.candidates_verified" => {stats.candidates_verified = count()?;}
+				"stats.sieve_verification_failures" => {stats.sieve_verification_failures = count()?;}
+				"stats.stale_tasks_discarded" => {stats.stale_tasks_discarded = count()?;}
+				"stats.trial_division_rejections" => {stats.trial_division_rejections = count()?;}
+				"stats.fermat_tests" => {stats.fermat_tests = count()?;}
+				"stats.confirmation_tests" => {stats.confirmation_tests = count()?;}
+				"stats.confirmation_failures" => {stats.confirmation_failures = count()?;}
+				"stats.elimination_counts" => {stats.elimination_counts = counts()?;}
+				"stats.disagreements" => {stats.disagreements = value.split(',').filter(|n| !n.is_empty()).map(|n| n.parse::<Integer>()).collect::<Result<Vec<Integer>, _>>().map_err(|_| invalid_value())?;}
+				"job" => {jobs.push(parse_job_state(value)?);}
+				_ => {return Err(format!("Unknown state field {}.", key));}
+			}
+		}
+		let fingerprint = fingerprint.ok_or("Missing fingerprint in the state.")?;
+		if fingerprint != stella.configuration_fingerprint() {
+			return Err(format!("The state was saved with the configuration {}, which differs from the one of the given parameters {}.", fingerprint, stella.configuration_fingerprint()));
+		}
+		if stats.tuple_counts.len() != stella.params.constellation_pattern.len() + 1 || stats.position_prime_counts.len() != stella.params.constellation_pattern.len() {
+			return Err("The tuple or position prime counts of the state do not match the constellation pattern.".to_string());
+		}
+		for (job, completed_until) in jobs {
+			let job_id = job.id;
+			let (job_warnings, errors) = stella.add_job_from(job, completed_until);
+			if !errors.is_empty() {
+				return Err(format!("Could not restore the Job {}: {}", job_id, errors.join(" ")));
+			}
+			warnings.extend(job_warnings);
+		}
+		stella.output_seq.store(output_seq, Ordering::SeqCst);
+		stella.resumed_stats = Some((stats, elapsed));
+		return Ok((stella, warnings));
+	}
+	
+	// Read-only snapshot of the state of the instance, the locks are taken one at a time and only for copying, so the workers are barely disturbed
+	pub fn debug_snapshot(&self) -> DebugSnapshot {
+		let mut active_jobs: Vec<usize> = self.jobs.lock().unwrap().keys().cloned().collect();
+		active_jobs.sort();
+		let (queued_sieve_tasks, queued_check_tasks) = {
+			let tasks = self.tasks.lock().unwrap();
+			let queued_sieve_tasks = tasks.iter().filter(|task| task.t == TaskType::Sieve).count();
+			(queued_sieve_tasks, tasks.len() - queued_sieve_tasks)
+		};
+		return DebugSnapshot {
+			prime_table_size: self.primes.len(),
+			largest_prime: self.primes.last().cloned(),
+			primorial_bits: self.primorial_bits(),
+			active_jobs: active_jobs,
+			queued_sieve_tasks: queued_sieve_tasks,
+			queued_check_tasks: queued_check_tasks,
+			busy_workers: self.busy_workers(),
+			queued_outputs: self.output.lock().unwrap().len(),
+			stats: self.stats()
+		};
+	}
+}
+
+// The workers and stats threads would otherwise keep running detached after the instance is gone
+impl Drop for Stella {
+	fn drop(&mut self) {
+		self.stop_workers();
+	}
+}
+
+// Prime table, modular inverses and primorial generated once and shared by several Stella instances, each having its own workers, jobs and outputs
+pub struct StellaShared {
+	params: Params,
+	primes: Arc<Vec<usize>>,
+	modular_inverses: Arc<Vec<usize>>,
+	primorial: Integer
+}
+
+impl StellaShared {
+	// Sets the parameters and generates the tables like init, returns the shared tables and the possible warnings about the parameters, or the initialization error
+	pub fn new(params: Params) -> Result<(StellaShared, Vec<String>), InitError> {
+		let mut stella = Stella::new();
+		let warnings = stella.set_params(params);
+		stella.init()?;
+		let shared = StellaShared {
+			params: stella.params.clone(),
+			primes: stella.primes.clone(),
+			modular_inverses: stella.modular_inverses.clone(),
+			primorial: stella.primorial.clone()
+		};
+		return Ok((shared, warnings));
+	}
+	
+	pub fn params(&self) -> Params {
+		return self.params.clone();
+	}
+	
+	// Initialized instance with the same parameters as the shared tables
+	pub fn spawn_instance(&self) -> Stella {
+		let mut stella = Stella::new();
+		stella.params = self.params.clone();
+		self.share_tables(&mut stella);
+		return stella;
+	}
+	
+	// Initialized instance with other parameters (pattern, workers, sieve size,...), which must give the same tables (prime_table_limit or prime_count, and primorial_number)
+	// Returns the instance and the possible warnings about the parameters, or an error if the parameters are not compatible
+	pub fn spawn_instance_with_params(&self, params: Params) -> Result<(Stella, Vec<String>), String> {
+		let mut stella = Stella::new();
+		let warnings = stella.set_params(params);
+		if stella.params.primorial_number != self.params.primorial_number {
+			return Err(format!("The Primorial Number {} differs from the one of the shared tables ({}).", stella.params.primorial_number, self.params.primorial_number));
+		}
+		let same_table = match stella.params.prime_count {
+			Some(prime_count) => self.params.prime_count == Some(prime_count),
+			None => self.params.prime_count.is_none() && stella.params.prime_table_limit == self.params.prime_table_limit
+		};
+		if !same_table {
+			return Err("The prime table limit or prime count differs from the one of the shared tables.".to_string());
+		}
+		self.share_tables(&mut stella);
+		return Ok((stella, warnings));
+	}
+	
+	fn share_tables(&self, stella: &mut Stella) {
+		stella.params.prime_table_limit = self.params.prime_table_limit; // Set by the generation if the table was defined by its prime count
+		stella.primes = self.primes.clone();
+		stella.modular_inverses = self.modular_inverses.clone();
+		stella.primorial = self.primorial.clone();
+		*stella.tables.lock().unwrap() = (self.primes.clone(), self.modular_inverses.clone());
+		stella.stats.lock().unwrap().prime_table_size = self.primes.len();
+	}
+}
+
+// Tracks a Job added with add_job_tracked, sharing the state of the Stella instance, so it can be used without borrowing it (for example from another thread)
+#[derive(Clone)]
+pub struct JobHandle {
+	job_id: usize,
+	jobs: Arc<Mutex<HashMap<usize, Job>>>,
+	tasks: Arc<Mutex<VecDeque<Task>>>,
+	idle_cv: Arc<Condvar>,
+	job_stats: Arc<Mutex<HashMap<usize, JobStats>>>,
+	job_windows: Arc<Mutex<HashMap<usize, JobWindows>>>,
+	output: Arc<Mutex<VecDeque<Output>>>,
+	outputs_held: Arc<AtomicBool>,
+	last_output_ns: Arc<Mutex<HashMap<usize, Integer>>>
+}
+
+impl JobHandle {
+	pub fn job_id(&self) -> usize {
+		return self.job_id;
+	}
+	
+	// Fraction of the primorial factors range that was sieved, like Stella::job_progress
+	pub fn progress(&self) -> f64 {
+		return self.job_stats.lock().unwrap().get(&self.job_id).map_or(0f64, |job_stats| job_stats.progress());
+	}
+	
+	// Whether the whole range was sieved and all its Candidates checked, so no more Output can come. False for a canceled Job
+	pub fn is_complete(&self) -> bool {
+		let primorial_factor_max = match self.job_stats.lock().unwrap().get(&self.job_id) {
+			Some(job_stats) => job_stats.primorial_factor_max,
+			None => return false
+		};
+		return self.job_windows.lock().unwrap().get(&self.job_id).is_some_and(|job_windows| job_windows.windows.is_empty() && job_windows.completed_until > 0 && job_windows.completed_until >= primorial_factor_max);
+	}
+	
+	// Pops the queued Outputs of this Job, in the order in which they were found, the ones of other Jobs stay in the queue
+	pub fn outputs(&self) -> Vec<Output> {
+		if self.outputs_held.load(Ordering::SeqCst) {
+			return vec![];
+		}
+		let mut outputs = vec![];
+		self.output.lock().unwrap().retain(|output| {
+			if output.job_id != self.job_id {
+				return true;
+			}
+			outputs.push(output.clone());
+			return false;
+		});
+		outputs.reverse(); // The newest Outputs are at the front of the queue
+		return outputs;
+	}
+	
+	// Removes the Job and its queued Tasks, the Tasks being processed are finished (their Outputs can still be retrieved with outputs)
+	pub fn cancel(&self) -> () {
+		self.jobs.lock().unwrap().remove(&self.job_id);
+		self.last_output_ns.lock().unwrap().remove(&self.job_id);
+		self.tasks.lock().unwrap().retain(|task| task.job_id != self.job_id);
+		self.idle_cv.notify_all();
+	}
+}
+
+// Wakes up a worker for a single new Task, waking up all the waiting workers would only make the others wait again
+// With dedicated workers, the woken up one may not be able to process it, so all of them must be woken up
+fn notify_workers(cv: &Condvar, params: &Params) {
+	if params.sieve_workers > 0 || params.check_workers > 0 {
+		cv.notify_all();
+	}
+	else {
+		cv.notify_one();
+	}
+}
+
+// Locks the Stats in the workers, adding the waiting time to lock_wait with the profile_locks feature
+fn lock_stats<'a>(stats: &'a Mutex<Stats>, lock_wait: &mut f64) -> MutexGuard<'a, Stats> {
+	#[cfg(feature = "profile_locks")]
+	{
+		let instant = Instant::now();
+		let guard = stats.lock().unwrap();
+		*lock_wait += time_since(instant);
+		return guard;
+	}
+	#[cfg(not(feature = "profile_locks"))]
+	{
+		let _ = lock_wait;
+		return stats.lock().unwrap();
+	}
+}
+
+// Marks the end of a Task processed by a worker and wakes up threads waiting for the instance to be idle
+fn finish_task(busy_workers: &Mutex<usize>, idle_cv: &Condvar) {
+	*busy_workers.lock().unwrap() -= 1;
+	idle_cv.notify_all();
 }
 
 // Measures how many s elapsed since the given instant
@@ -460,6 +2922,31 @@ pub fn formatted_duration(duration : f64) -> String {
 	else {return format!("{:.3} y", duration/31556952f64);}
 }
 
+// 64 bits FNV-1a hash, used instead of the Rust Hashers when the value must not change between versions or runs
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+	let mut hash = 0xcbf29ce484222325u64;
+	for byte in bytes {
+		hash ^= *byte as u64;
+		hash = hash.wrapping_mul(0x100000001b3);
+	}
+	return hash;
+}
+
+// Get a compact representation of a possibly huge Integer, in scientific notation if it has more than 18 digits
+pub fn formatted_integer(n: &Integer) -> String {
+	if n.clone().abs() < 1e18 {return format!("{}", n);}
+	else {return format!("{:.12e}", n);}
+}
+
+// Estimates how many primes are lower or equal to limit, without generating them
+// Uses the asymptotic expansion π(x) ≈ x/ln(x) × (1 + 1/ln(x) + 2/ln(x)²), which is within about 1 % for limits above 10^5 (but rougher for small ones)
+pub fn prime_count_estimate(limit: usize) -> usize {
+	if limit < 2 {return 0;}
+	let x = limit as f64;
+	let ln_x = x.ln();
+	return (x/ln_x*(1f64 + 1f64/ln_x + 2f64/(ln_x*ln_x))).round() as usize;
+}
+
 // Generate all the prime numbers from 2 to limit inclusive with optimized Sieve of Eratosthenes (for 64 bits machines)
 fn generate_primes(limit: usize) -> Vec<usize> {
 	if limit < 2 {return Vec::new()};
@@ -480,7 +2967,7 @@ fn generate_primes(limit: usize) -> Vec<usize> {
 	
 	let mut prime_table: Vec<usize> = vec![2];
 	let mut i = 1;
-	while (i << 1) + 1 <= limit { // Fill the prime table using the composite table
+	while (i << 1) < limit { // Fill the prime table using the composite table
 		if (composite_table[i >> 6] & (1 << (i & 63))) == 0 {
 			prime_table.push((i << 1) + 1); // Add prime number 2i + 1
 		}
@@ -489,8 +2976,38 @@ fn generate_primes(limit: usize) -> Vec<usize> {
 	return prime_table;
 }
 
+// Generate the n first prime numbers
+// The limit is estimated with the upper bound p_n < n × (ln(n) + ln(ln(n))) for n >= 6, then the extra primes are removed
+pub fn generate_first_n_primes(n: usize) -> Vec<usize> {
+	let mut limit = if n < 6 {13} else {
+		let x = n as f64;
+		(x*(x.ln() + x.ln().ln())).ceil() as usize
+	};
+	loop {
+		let mut primes = generate_primes(limit);
+		if primes.len() >= n {
+			primes.truncate(n);
+			return primes;
+		}
+		limit *= 2; // Should not happen, but just in case of floating point inaccuracies
+	}
+}
+
+// The primorial being the product of the primorial_number - 1 first primes, the sieve is done with the next ones, unless more primes are excluded with unsieved_primes
+fn first_sieving_prime_index(params: &Params) -> usize {
+	return std::cmp::max(params.primorial_number.saturating_sub(1), params.unsieved_primes);
+}
+
+// Number of primorial factors processed by a Sieve Task
+fn sieve_window_size(params: &Params) -> usize {
+	return match params.max_factors_per_sieve_task {
+		0 => params.sieve_size,
+		max_factors_per_sieve_task => std::cmp::min(params.sieve_size, max_factors_per_sieve_task)
+	};
+}
+
 // Computes the primorial_numberth primorial, a Vec containing enough prime numbers must be provided
-fn primorial(primes: &Vec<usize>, primorial_number: usize) -> Integer {
+fn primorial(primes: &[usize], primorial_number: usize) -> Integer {
 	let mut primorial = Integer::from(1);
 	for i in 1 .. primorial_number {
 		primorial *= primes[i - 1];
@@ -500,19 +3017,1015 @@ fn primorial(primes: &Vec<usize>, primorial_number: usize) -> Integer {
 
 // Computes the modular inverses a^(-1) of the integer a with respect to moduli m: a × a^(-1) ≡ 1 (mod m)
 // Sets 0 if the inverse does not exist
-fn compute_modular_inverses(a: &Integer, moduli: &Vec<usize>) -> Vec<usize> {
+fn compute_modular_inverses(a: &Integer, moduli: &[usize]) -> Vec<usize> {
 	let mut inverses = vec![0; moduli.len()];
 	for i in 0 .. moduli.len() {
-		match a.invert_ref(&Integer::from(moduli[i])) {
-			Some(inverse) => inverses[i] = Integer::from(inverse).to_usize().unwrap(),
-			_ => {}
-		};
+		if let Some(inverse) = a.invert_ref(&Integer::from(moduli[i])) {
+			inverses[i] = Integer::from(inverse).to_usize().unwrap_or(0); // Lower than the modulus, so it always fits
+		}
 	}
 	return inverses;
 }
 
+// Base and stride of the Candidates of a Job, which have the form base + f × stride
+// The base is the first number above the next primorial multiple (or at or above target_min with exact_range_start) congruent to the Primorial Offset modulo the primorial and, if the Job has an alignment, to target_min modulo the alignment (Chinese Remainder Theorem)
+fn candidate_lattice(job: &Job, primorial: &Integer, primorial_offset: u128, exact_range_start: bool) -> Result<(Integer, Integer), String> {
+	let mut base = job.target_min.clone() + primorial - (job.target_min.clone() % primorial) + primorial_offset;
+	if exact_range_start || job.target_max == job.target_min { // A point search must test the first Candidate at or above the target. The offset can exceed the primorial, only its remainder matters
+		base = job.target_min.clone() - (job.target_min.clone() % primorial) + (Integer::from(primorial_offset) % primorial);
+		if base < job.target_min {
+			base += primorial;
+		}
+	}
+	let alignment = match &job.alignment {
+		Some(alignment) => alignment,
+		None => {return Ok((base, primorial.clone()));}
+	};
+	if *alignment <= 0 {
+		return Err("The alignment must be positive.".to_string());
+	}
+	let gcd = primorial.clone().gcd(alignment);
+	let difference = Integer::from(&job.target_min - &base);
+	if !difference.is_divisible(&gcd) {
+		return Err(format!("The alignment {} is incompatible with the Primorial Offset, as it shares the factor {} with the primorial but target_min is not congruent to the Candidates modulo it.", alignment, gcd).to_string());
+	}
+	// Find t such that base + t × primorial ≡ target_min (mod alignment), that is primorial/gcd × t ≡ (target_min - base)/gcd (mod alignment/gcd)
+	// The primorial being squarefree, primorial/gcd and alignment/gcd are coprime
+	let reduced_alignment = alignment.clone()/&gcd;
+	let inverse = (primorial.clone()/&gcd).invert(&reduced_alignment).unwrap_or_default(); // 0 if the reduced alignment is 1
+	let t = ((difference/&gcd)*inverse).rem_euc(&reduced_alignment);
+	return Ok((base + primorial.clone()*t, primorial.clone()*reduced_alignment));
+}
+
+// Index of the first word from start having at least one non eliminated factor, or words.len() if there is none
+fn next_word_with_candidates(words: &[usize], start: usize) -> usize {
+	#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+	{
+		if is_x86_feature_detected!("avx2") {
+			return unsafe {next_word_with_candidates_avx2(words, start)};
+		}
+	}
+	return next_word_with_candidates_scalar(words, start);
+}
+
+fn next_word_with_candidates_scalar(words: &[usize], start: usize) -> usize {
+	let mut i = start;
+	while i < words.len() && words[i] == usize::MAX {
+		i += 1;
+	}
+	return i;
+}
+
+// Compares blocks of 4 words at once to quickly skip the regions where everything was eliminated, then finishes with the scalar version
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn next_word_with_candidates_avx2(words: &[usize], start: usize) -> usize {
+	use std::arch::x86_64::*;
+	let all_eliminated = _mm256_set1_epi64x(-1);
+	let mut i = start;
+	while i + 4 <= words.len() {
+		let block = _mm256_loadu_si256(words.as_ptr().add(i) as *const __m256i);
+		if _mm256_movemask_epi8(_mm256_cmpeq_epi64(block, all_eliminated)) != -1 {
+			break;
+		}
+		i += 4;
+	}
+	return next_word_with_candidates_scalar(words, i);
+}
+
+// Finds a prime p among the given ones dividing first_candidate + factor × stride + offset, using the precomputed remainders of first_candidate and stride modulo these primes
+fn small_prime_factor(primes: &[usize], first_candidate_remainders: &[usize], stride_remainders: &[usize], factor: usize, offset: isize) -> Option<usize> {
+	for j in 0 .. primes.len() {
+		let p = primes[j];
+		if (first_candidate_remainders[j] + (factor % p)*stride_remainders[j] + offset.rem_euclid(p as isize) as usize).is_multiple_of(p) {
+			return Some(p);
+		}
+	}
+	return None;
+}
+
 // n is probably prime if a^(n - 1) ≡ 1 (mod n) for one 0 < a < p or more (a = 2 is used here)
 // Used for quick primality testing, outputs should be checked with an appropriate test.
+// Numbers below 4 are handled separately, as the test does not make sense for them (2 and 3 would fail and 1 or 0 are not valid moduli).
 fn is_prime_fermat(n: &Integer) -> bool {
-	return Integer::from(2).pow_mod(&(n - Integer::from(1)), &n).unwrap() == 1;
+	if *n < 4 {return *n == 2 || *n == 3;}
+	return Integer::from(2).pow_mod(&(n - Integer::from(1)), n).unwrap() == 1;
+}
+
+// n is probably prime if a^((n - 1)/2) ≡ (a/n) (mod n), with (a/n) the Jacobi symbol, for one 0 < a < n or more (a = 2 is used here)
+// Stronger than the Fermat test with the same base, the odd composite numbers passing it also pass the Fermat test, but not the converse.
+fn is_prime_euler(n: &Integer) -> bool {
+	if *n < 4 {return *n == 2 || *n == 3;}
+	if n.is_even() {return false;}
+	let residue = Integer::from(2).pow_mod(&(Integer::from(n - 1u32) >> 1), n).unwrap();
+	return match Integer::from(2).jacobi(n) {
+		1 => residue == 1,
+		-1 => residue == Integer::from(n - 1u32),
+		_ => false
+	};
+}
+
+// Whether all the numbers n + offset of the tuple are prime according to GMP's is_probably_prime, a much stronger test than the Fermat ones of the search
+// Since GMP 6.2, it does trial divisions, a Baillie-PSW test (no counterexample is known), then reps - 24 Miller-Rabin rounds if reps > 24, each letting a composite number pass with a probability of at most 1/4
+pub fn verify_constellation_strong(n: &Integer, pattern: &[isize], reps: i32) -> bool {
+	return pattern.iter().all(|&offset| Integer::from(n + offset).is_probably_prime(reps.max(0) as u32) != rug::integer::IsPrime::No);
+}
+
+// Miller-Rabin primality test with the given number of random bases, a composite number passes each round with a probability of at most 1/4
+pub fn is_prime_miller_rabin(n: &Integer, rounds: usize, rand: &mut RandState) -> bool {
+	if *n < 4 {return *n == 2 || *n == 3;}
+	if n.is_even() {return false;}
+	// n - 1 = d × 2^s with d odd
+	let n_minus_one = Integer::from(n - 1u32);
+	let s = n_minus_one.find_one(0).unwrap();
+	let d = Integer::from(&n_minus_one >> s);
+	for _ in 0 .. rounds {
+		let base = Integer::from(Integer::from(n - 3u32).random_below_ref(rand)) + 2u32; // From 2 to n - 2
+		let mut x = base.pow_mod(&d, n).unwrap();
+		if x == 1 || x == n_minus_one {continue;}
+		let mut witness = true;
+		for _ in 1 .. s {
+			x = x.pow_mod(&Integer::from(2), n).unwrap();
+			if x == n_minus_one {
+				witness = false;
+				break;
+			}
+		}
+		if witness {return false;}
+	}
+	return true;
+}
+
+// Seed taken from the randomly keyed hasher of the standard library
+fn entropy_seed() -> u64 {
+	let mut hasher = RandomState::new().build_hasher();
+	hasher.write_u128(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |duration| duration.as_nanos()));
+	return hasher.finish();
+}
+
+// Same as is_prime_fermat, for numbers fitting in 64 bits
+fn is_prime_fermat_u64(n: u64) -> bool {
+	if n < 4 {return n == 2 || n == 3;}
+	let n = n as u128;
+	let (mut result, mut base, mut exponent) = (1 % n, 2 % n, n - 1);
+	while exponent > 0 {
+		if exponent & 1 == 1 {
+			result = (result*base) % n;
+		}
+		base = (base*base) % n;
+		exponent >>= 1;
+	}
+	return result == 1;
+}
+
+// Same as is_prime_euler, for numbers fitting in 64 bits
+fn is_prime_euler_u64(n: u64) -> bool {
+	if n < 4 {return n == 2 || n == 3;}
+	if n.is_multiple_of(2) {return false;}
+	let n = n as u128;
+	let (mut result, mut base, mut exponent) = (1 % n, 2 % n, (n - 1)/2);
+	while exponent > 0 {
+		if exponent & 1 == 1 {
+			result = (result*base) % n;
+		}
+		base = (base*base) % n;
+		exponent >>= 1;
+	}
+	// The Jacobi symbol (2/n) is 1 if n ≡ ±1 (mod 8) and -1 if n ≡ ±3 (mod 8)
+	return if n % 8 == 1 || n % 8 == 7 {result == 1} else {result == n - 1};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	const SEPTUPLET: [isize; 7] = [0, 2, 6, 8, 12, 18, 20];
+	
+	// Small configuration searching quickly even without optimizations
+	fn test_params() -> Params {
+		return Params {
+			workers: 4,
+			constellation_pattern: SEPTUPLET.to_vec(),
+			prime_table_limit: 1 << 12,
+			primorial_number: 12,
+			sieve_size: 1 << 12,
+			..Default::default()
+		};
+	}
+	
+	fn test_job(id: usize) -> Job {
+		return Job::builder(id, SEPTUPLET.to_vec())
+			.target_min(Integer::from(1) << 64)
+			.target_max((Integer::from(1) << 64) + (Integer::from(1) << 58))
+			.k_min(4)
+			.pattern_min(vec![true, true, false, false, false, false, false])
+			.build()
+			.unwrap();
+	}
+	
+	// Runs the Job on a new instance with the given parameters and returns the outputs sorted and the final Stats
+	fn search_with_stats(params: Params, job: Job) -> (Vec<Output>, Stats) {
+		let mut stella = Stella::new();
+		stella.set_params(params);
+		stella.init().unwrap();
+		stella.start_workers();
+		let (_, errors) = stella.add_job(job);
+		assert!(errors.is_empty(), "{:?}", errors);
+		assert!(stella.wait_idle(Duration::from_secs(60)));
+		stella.stop_workers();
+		let stats = stella.stats();
+		return (stella.drain_outputs_sorted(), stats);
+	}
+	
+	fn search(params: Params, job: Job) -> Vec<Output> {
+		return search_with_stats(params, job).0;
+	}
+	
+	#[test]
+	fn check_orders_give_the_same_outputs() {
+		let reference = search(test_params(), test_job(1));
+		assert!(!reference.is_empty());
+		for output in reference.iter() {
+			assert!(output.pattern.starts_with(&[0, 2]) && output.pattern.len() >= 4);
+		}
+		let outputs = search(Params {check_order: CheckOrder::Centered, ..test_params()}, test_job(1));
+		assert_eq!(outputs, reference);
+		for (output, reference_output) in outputs.iter().zip(reference.iter()) {
+			assert_eq!(output.pattern, reference_output.pattern);
+		}
+	}
+	
+	#[test]
+	fn calibrated_check_order_gives_the_same_outputs() {
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		let probabilities = stella.calibrate(&test_job(1), 2000);
+		assert_eq!(probabilities.len(), SEPTUPLET.len());
+		assert!(probabilities.iter().all(|probability| (0f64 ..= 1f64).contains(probability)));
+		let reference = search(test_params(), test_job(1));
+		assert_eq!(search(Params {check_order: CheckOrder::Probabilities(probabilities), ..test_params()}, test_job(1)), reference);
+		// The positions required by pattern_min last
+		assert_eq!(search(Params {check_order: CheckOrder::Probabilities(vec![1f64, 1f64, 0.5, 0.5, 0.5, 0.5, 0.5]), ..test_params()}, test_job(1)), reference);
+	}
+	
+	#[test]
+	fn stopped_workers_are_not_revived() {
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		for id in 1 .. 4 {
+			stella.start_workers();
+			assert_eq!(stella.worker_threads.len(), 4);
+			stella.add_job(Job {clear_previous_jobs: true, ..test_job(id)});
+			thread::sleep(Duration::from_millis(20));
+			stella.stop_workers();
+			// The workers exited, so nothing is processed anymore
+			assert!(stella.worker_threads.is_empty());
+			assert_eq!(stella.busy_workers(), 0);
+			let queued_tasks = stella.queued_tasks();
+			thread::sleep(Duration::from_millis(50));
+			assert_eq!(stella.queued_tasks(), queued_tasks);
+		}
+	}
+	
+	#[test]
+	fn load_state_resumes_with_the_same_parameters_only() {
+		let path = std::env::temp_dir().join(format!("stella_test_state_{}.txt", std::process::id()));
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		stella.start_workers();
+		let (_, errors) = stella.add_job(Job {target_max: (Integer::from(1) << 64) + (Integer::from(1) << 72), alignment: Some(Integer::from(4099)), factor_stride: Some((3, 1)), ..test_job(1)});
+		assert!(errors.is_empty(), "{:?}", errors);
+		thread::sleep(Duration::from_millis(100));
+		stella.stop_workers();
+		stella.save_state(&path).unwrap();
+		let checkpoint = stella.checkpoint(1).unwrap();
+		let stats = stella.stats();
+		for params in [
+			Params {exact_range_start: true, ..test_params()},
+			Params {unsieved_primes: 13, ..test_params()},
+			Params {prime_table_limit: 0, prime_count: Some(564), ..test_params()}, // The same primes (up to 4096), but defined by their count
+			Params {sieve_size: 1 << 13, ..test_params()},
+			Params {primorial_number: 13, ..test_params()}
+		] {
+			assert!(Stella::load_state(&path, params).is_err());
+		}
+		let (mut resumed_stella, _) = Stella::load_state(&path, test_params()).unwrap();
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(resumed_stella.checkpoint(1).unwrap().completed_until, checkpoint.completed_until);
+		let (resumed_stats, _) = resumed_stella.resumed_stats.clone().unwrap();
+		assert_eq!(resumed_stats.candidates_tested, stats.candidates_tested);
+		assert_eq!(resumed_stats.tuple_counts, stats.tuple_counts);
+		resumed_stella.start_workers(); // The workers continue the search, so the counts can only grow
+		assert!(resumed_stella.stats().candidates_tested >= stats.candidates_tested);
+		resumed_stella.stop_workers();
+		let resumed_job = resumed_stella.jobs.lock().unwrap().get(&1).cloned().unwrap();
+		assert_eq!(resumed_job.alignment, Some(Integer::from(4099)));
+		assert_eq!(resumed_job.factor_stride, Some((3, 1)));
+	}
+	
+	#[test]
+	fn trial_divisions_only_use_the_primes_excluded_from_the_sieve() {
+		let (reference, stats) = search_with_stats(test_params(), test_job(1));
+		assert_eq!(stats.trial_division_rejections, 0);
+		// Primes excluded from the sieve are left to the trial divisions. The sieve eliminates whole tuples while the trial divisions reject single numbers, so the tuples having enough other primes are found in addition
+		let (outputs, stats) = search_with_stats(Params {unsieved_primes: 16, ..test_params()}, test_job(1));
+		assert!(stats.trial_division_rejections > 0);
+		assert!(outputs.len() > reference.len());
+		for output in reference.iter() {
+			assert!(outputs.contains(output));
+		}
+		for output in outputs.iter() {
+			assert!(verify_constellation_strong(&output.n, &output.pattern, 25));
+		}
+	}
+	
+	#[test]
+	fn trial_divisions_cover_the_unsieved_primes() {
+		let mut stella = Stella::new();
+		assert_eq!(stella.set_params(Params {unsieved_primes: 5, ..test_params()}).len(), 1); // Fewer than the primes of the primorial
+		assert_eq!(stella.params.unsieved_primes, 0);
+		assert!(stella.set_params(Params {unsieved_primes: 16, ..test_params()}).is_empty());
+		assert_eq!(stella.params.trial_division_prime_count, 5);
+		assert_eq!(stella.set_params(Params {unsieved_primes: 16, trial_division_prime_count: 4, ..test_params()}).len(), 1);
+		assert!(stella.set_params(Params {unsieved_primes: 16, trial_division_prime_count: 5, ..test_params()}).is_empty());
+		assert!(stella.set_params(test_params()).is_empty());
+		assert_eq!(stella.params.trial_division_prime_count, 0);
+	}
+	
+	#[test]
+	fn too_big_prime_table_limits_give_errors() {
+		let mut stella = Stella::new();
+		assert_eq!(stella.set_params(Params {prime_table_limit: MAX_PRIME_TABLE_LIMIT + 1, ..test_params()}).len(), 1);
+		assert_eq!(stella.params.prime_table_limit, MAX_PRIME_TABLE_LIMIT);
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		assert!(stella.extend_prime_table(MAX_PRIME_TABLE_LIMIT + 1).is_err());
+		assert_eq!(stella.params.prime_table_limit, 1 << 12);
+		assert!(stella.extend_prime_table(1 << 13).is_ok());
+		assert_eq!(stella.primes.len(), 1028);
+	}
+	
+	#[test]
+	fn dropped_outputs_do_not_update_the_dedup_states() {
+		// With a single worker, the Candidates are tested in increasing order, so the spacing is applied greedily to the sorted outputs
+		let params = Params {workers: 1, ..test_params()};
+		let reference = search(params.clone(), test_job(1));
+		let spacing = Integer::from(1) << 50;
+		let mut filter = BloomFilter::with_capacity(1000, 1e-9);
+		filter.insert(&reference[0].n); // Already reported, so its neighbours must not be dropped for being too close to it
+		let mut expected: Vec<Integer> = vec![];
+		for output in reference.iter().skip(1) {
+			if expected.last().is_none_or(|last| Integer::from(&output.n - last) >= spacing) {
+				expected.push(output.n.clone());
+			}
+		}
+		assert!(Integer::from(&reference[1].n - &reference[0].n) < spacing);
+		let mut stella = Stella::new();
+		stella.set_params(params);
+		stella.init().unwrap();
+		stella.set_dedup_filter(Some(filter));
+		stella.start_workers();
+		let (_, errors) = stella.add_job(Job {min_output_spacing: spacing, dedup_outputs: true, ..test_job(1)});
+		assert!(errors.is_empty(), "{:?}", errors);
+		assert!(stella.wait_idle(Duration::from_secs(60)));
+		stella.stop_workers();
+		let outputs: Vec<Integer> = stella.drain_outputs_sorted().into_iter().map(|output| output.n).collect();
+		assert_eq!(outputs, expected);
+		assert!(expected.iter().all(|n| stella.dedup_filter().unwrap().contains(n)));
+	}
+	
+	#[test]
+	fn full_event_queue_drops_the_oldest_events() {
+		let events = Mutex::new(VecDeque::new());
+		let dropped_events = AtomicUsize::new(0);
+		for i in 0 .. MAX_QUEUED_EVENTS + 10 {
+			push_event(&events, &dropped_events, Event::Heartbeat(Stats {candidates_tested: i, ..Stats::default()}));
+		}
+		let mut events = events.into_inner().unwrap();
+		assert_eq!(events.len(), MAX_QUEUED_EVENTS);
+		assert_eq!(dropped_events.load(Ordering::SeqCst), 10);
+		match events.pop_back() {
+			Some(Event::Heartbeat(stats)) => assert_eq!(stats.candidates_tested, 10),
+			None => panic!("No event queued.")
+		}
+	}
+	
+	#[test]
+	fn held_outputs_pause_the_workers_without_losing_outputs() {
+		let reference = search(test_params(), test_job(1));
+		let mut stella = Stella::new();
+		stella.set_params(Params {max_held_outputs: 5, ..test_params()});
+		stella.init().unwrap();
+		stella.hold_outputs();
+		stella.start_workers();
+		let (_, errors) = stella.add_job(test_job(1));
+		assert!(errors.is_empty(), "{:?}", errors);
+		assert!(!stella.wait_idle(Duration::from_millis(500)));
+		let held_outputs = stella.output.lock().unwrap().len();
+		assert!((5 .. reference.len()).contains(&held_outputs));
+		assert!(stella.pop_output().is_none());
+		stella.release_outputs();
+		assert!(stella.wait_idle(Duration::from_secs(60)));
+		stella.stop_workers();
+		assert_eq!(stella.drain_outputs_sorted(), reference);
+	}
+	
+	#[test]
+	fn point_search_finds_the_tuple_at_the_target() {
+		// The Candidate at the target is tested even without exact_range_start
+		let reference = search(test_params(), test_job(1));
+		for params in [test_params(), Params {exact_range_start: true, ..test_params()}] {
+			for output in [&reference[0], &reference[reference.len()/2]] {
+				let outputs = search(params.clone(), Job {target_min: output.n.clone(), target_max: output.n.clone(), ..test_job(1)});
+				assert_eq!(outputs, vec![output.clone()]);
+			}
+			let outputs = search(params, Job {target_min: reference[0].n.clone() + 1, target_max: reference[0].n.clone() + 1, ..test_job(1)});
+			assert!(!outputs.contains(&reference[0]));
+		}
+		// The only Candidate has the primorial factor 0, so only the share of the offset 0 checks it
+		let point_job = Job {target_min: reference[0].n.clone(), target_max: reference[0].n.clone(), ..test_job(1)};
+		assert_eq!(search(test_params(), Job {factor_stride: Some((2, 0)), ..point_job.clone()}), vec![reference[0].clone()]);
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		stella.start_workers();
+		let (handle, warnings) = stella.add_job_tracked(Job {factor_stride: Some((2, 1)), ..point_job}).unwrap();
+		assert_eq!(warnings.len(), 1);
+		assert!(stella.wait_idle(Duration::from_secs(60)));
+		assert!(handle.is_complete());
+		assert!(handle.outputs().is_empty());
+		stella.stop_workers();
+	}
+	
+	#[test]
+	fn native_arithmetic_gives_the_same_outputs() {
+		for test in [PrimalityTest::Fermat, PrimalityTest::Euler] {
+			for n in (0u64 .. 10000).chain(u64::MAX - 10000 ..= u64::MAX) {
+				assert_eq!(test.is_probable_prime_u64(n), test.is_probable_prime(&Integer::from(n)), "{}", n);
+			}
+		}
+		// The first Job fits in 64 bits and uses native integers, the second one not, its outputs beyond the range of the first one being filtered out
+		let native_job = Job {target_min: (Integer::from(1) << 64) - (Integer::from(1) << 58), target_max: Integer::from(u64::MAX), ..test_job(1)};
+		let big_job = Job {target_max: Integer::from(1) << 64, ..native_job.clone()};
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		let (_, last_native_candidate) = stella.candidate_range(&native_job);
+		let native_outputs = search(test_params(), native_job);
+		assert!(!native_outputs.is_empty());
+		let big_outputs: Vec<Output> = search(test_params(), big_job).into_iter().filter(|output| output.n <= last_native_candidate).collect();
+		assert_eq!(native_outputs, big_outputs);
+	}
+	
+	#[test]
+	fn default_offset_for_known_patterns_only() {
+		assert_eq!(default_offset_for(&SEPTUPLET), Some(380284918609481));
+		assert_eq!(default_offset_for(&[0, 4, 6, 10, 12, 16]), Some(1091257));
+		assert_eq!(default_offset_for(&[0, 2, 4]), None); // Not admissible
+		assert_eq!(default_offset_for(&[0, 6, 12]), None);
+		assert_eq!(default_offset_for(&[]), None);
+	}
+	
+	#[test]
+	fn shifted_pattern_finds_its_default_offset() {
+		let shifted_pattern: Vec<isize> = SEPTUPLET.iter().map(|offset| offset + 2).collect();
+		assert_eq!(default_offset_for(&shifted_pattern), Some(380284918609481 - 2));
+		assert_eq!(default_offset_for(&[6, 0, 2]), default_offset_for(&[0, 2, 6]));
+		let mut stella = Stella::new();
+		stella.set_params(Params {constellation_pattern: shifted_pattern.clone(), ..test_params()});
+		assert_eq!(stella.params.primorial_offset, 380284918609481 - 2);
+		// The same tuples are found, their base numbers being shifted
+		let reference = search(test_params(), test_job(1));
+		let outputs = search(Params {constellation_pattern: shifted_pattern.clone(), ..test_params()}, Job {pattern: shifted_pattern, ..test_job(1)});
+		assert_eq!(outputs.len(), reference.len());
+		for (output, reference_output) in outputs.iter().zip(reference.iter()) {
+			assert_eq!(Integer::from(&output.n + 2), reference_output.n);
+			assert_eq!(output.pattern, reference_output.pattern.iter().map(|offset| offset + 2).collect::<Vec<isize>>());
+		}
+	}
+	
+	#[test]
+	fn fermat_test_handles_small_numbers() {
+		for (n, is_prime) in [(0u64, false), (1, false), (2, true), (3, true), (4, false)] {
+			assert_eq!(is_prime_fermat(&Integer::from(n)), is_prime, "{}", n);
+			assert_eq!(is_prime_fermat_u64(n), is_prime, "{}", n);
+		}
+	}
+	
+	#[test]
+	fn pattern_by_name_knows_the_usual_names() {
+		assert_eq!(pattern_by_name("twin"), Some(vec![0, 2]));
+		assert_eq!(pattern_by_name("Prime Sextuplet"), Some(vec![0, 4, 6, 10, 12, 16]));
+		assert_eq!(pattern_by_name(" septuplet "), Some(SEPTUPLET.to_vec()));
+		assert_eq!(pattern_by_name("prime gap"), None);
+		assert_eq!(pattern_by_name(""), None);
+		for (name, pattern) in PATTERN_NAMES {
+			assert!(default_offset_for(pattern).is_some(), "{}", name);
+		}
+	}
+	
+	#[test]
+	fn clearing_the_previous_jobs_drops_their_tasks() {
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		for id in [1, 2] {
+			let (_, errors) = stella.add_job(test_job(id));
+			assert!(errors.is_empty(), "{:?}", errors);
+		}
+		assert_eq!(stella.queued_tasks(), 2);
+		let (_, errors) = stella.add_job(Job {clear_previous_jobs: true, ..test_job(3)});
+		assert!(errors.is_empty(), "{:?}", errors);
+		assert!(stella.tasks.lock().unwrap().iter().all(|task| task.job_id == 3));
+		assert_eq!(stella.queued_tasks(), 1);
+		assert_eq!(stella.jobs.lock().unwrap().keys().collect::<Vec<&usize>>(), vec![&3]);
+	}
+	
+	#[cfg(feature = "simd")]
+	#[test]
+	fn simd_scan_gives_the_same_candidates_as_the_scalar_one() {
+		// Mostly fully eliminated words, like in a real sieve, with various lengths to cover the scalar tail
+		let mut state = 1u64;
+		for length in 0 .. 64 {
+			let words: Vec<usize> = (0 .. length).map(|_| {
+				state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+				if (state >> 33).is_multiple_of(8) {(state >> 7) as usize} else {usize::MAX}
+			}).collect();
+			let candidates = |next_word: &dyn Fn(&[usize], usize) -> usize| -> Vec<usize> {
+				let mut candidates = vec![];
+				let mut i = next_word(&words, 0);
+				while i < words.len() {
+					candidates.extend((0 .. WORD_SIZE).filter(|b| words[i] & (1 << b) == 0).map(|b| i*WORD_SIZE + b));
+					i = next_word(&words, i + 1);
+				}
+				return candidates;
+			};
+			assert_eq!(candidates(&next_word_with_candidates), candidates(&next_word_with_candidates_scalar));
+		}
+	}
+	
+	#[test]
+	fn sieve_tasks_stay_in_the_sieve_for_any_window() {
+		// The ranges end in the middle of a word, and are smaller or bigger than the sieve. A write beyond the sieve would panic in a worker, which then never finishes its Task
+		for range_bits in [50, 53] {
+			let job = Job {target_max: (Integer::from(1) << 64) + (Integer::from(1) << range_bits), ..test_job(1)};
+			let (reference, reference_stats) = search_with_stats(test_params(), job.clone());
+			assert!(reference_stats.candidates_generated > 0);
+			for sieve_size in [64, 128, 1 << 10] {
+				for max_factors_per_sieve_task in [0, 100] {
+					let params = Params {sieve_size: sieve_size, max_factors_per_sieve_task: max_factors_per_sieve_task, verify_sieve: true, verify_sieve_interval: 1, ..test_params()};
+					let (outputs, stats) = search_with_stats(params, job.clone());
+					assert_eq!(stats.sieve_verification_failures, 0);
+					assert_eq!(stats.candidates_generated, reference_stats.candidates_generated);
+					assert_eq!(outputs, reference);
+				}
+			}
+		}
+	}
+	
+	#[test]
+	fn overlapping_jobs_with_the_same_pattern_give_a_warning() {
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		let overlap_warnings = |warnings: Vec<String>| warnings.iter().filter(|warning| warning.contains("overlaps")).count();
+		let (warnings, errors) = stella.add_job(test_job(1));
+		assert!(errors.is_empty(), "{:?}", errors);
+		assert_eq!(overlap_warnings(warnings), 0);
+		let (warnings, _) = stella.add_job(Job {target_min: test_job(1).target_max - 1, target_max: test_job(1).target_max << 1, ..test_job(2)});
+		assert_eq!(overlap_warnings(warnings), 1);
+		// Not overlapping, another pattern, or replacing the previous Jobs
+		let (warnings, _) = stella.add_job(Job {target_min: test_job(1).target_max << 2, target_max: test_job(1).target_max << 3, ..test_job(3)});
+		assert_eq!(overlap_warnings(warnings), 0);
+		let (warnings, _) = stella.add_job(Job {pattern: vec![0, 2, 6, 8], pattern_min: vec![true; 4], ..test_job(4)});
+		assert_eq!(overlap_warnings(warnings), 0);
+		let (warnings, _) = stella.add_job(Job {clear_previous_jobs: true, ..test_job(5)});
+		assert_eq!(overlap_warnings(warnings), 0);
+	}
+	
+	#[test]
+	fn exact_range_start_does_not_miss_the_first_candidates() {
+		let params = Params {exact_range_start: true, ..test_params()};
+		let mut stella = Stella::new();
+		stella.set_params(params.clone());
+		stella.init().unwrap();
+		let primorial = stella.primorial.clone();
+		for target_min in [test_job(1).target_min, test_job(1).target_min + 1, test_job(1).target_min + &primorial - 1, Integer::from(380284918609481u64)] {
+			let (first_candidate, _) = stella.candidate_range(&Job {target_min: target_min.clone(), ..test_job(1)});
+			assert!(first_candidate >= target_min && first_candidate < Integer::from(&target_min + &primorial));
+			assert_eq!(Integer::from(&first_candidate % &primorial), Integer::from(380284918609481u64) % &primorial);
+		}
+		// A tuple just above target_min is found
+		let reference = search(params.clone(), test_job(1));
+		let outputs = search(params, Job {target_min: reference[0].n.clone() - 1, ..test_job(1)});
+		assert_eq!(outputs.first(), reference.first());
+	}
+	
+	#[test]
+	fn too_wide_patterns_give_an_error() {
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		let wide_job = |id: usize, last_offset: isize| Job {pattern: vec![0, 2, last_offset], pattern_min: vec![true; 3], k_min: 3, ..test_job(id)};
+		let (_, errors) = stella.add_job(wide_job(1, isize::MAX));
+		assert_eq!(errors.len(), 1, "{:?}", errors);
+		assert!(errors[0].contains("too wide"));
+		let (_, errors) = stella.add_job(wide_job(2, -(stella.primorial.to_isize().unwrap())));
+		assert_eq!(errors.len(), 1, "{:?}", errors);
+		assert!(stella.jobs.lock().unwrap().is_empty());
+		let (_, errors) = stella.add_job(wide_job(3, 6));
+		assert!(errors.is_empty(), "{:?}", errors);
+	}
+	
+	#[test]
+	fn decompose_inverts_the_candidate_generation() {
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		let job = test_job(1);
+		let (first_candidate, _) = stella.candidate_range(&job);
+		assert_eq!(stella.decompose(&first_candidate, &job), Some((0, Integer::from(0))));
+		for output in search(test_params(), job.clone()) {
+			let (primorial_factor, offset) = stella.decompose(&output.n, &job).unwrap();
+			assert_eq!(offset, 0);
+			assert_eq!(&first_candidate + primorial_factor*stella.primorial.clone(), output.n);
+			for &pattern_offset in output.pattern.iter() {
+				assert_eq!(stella.decompose(&Integer::from(&output.n + pattern_offset), &job), Some((primorial_factor, Integer::from(pattern_offset))));
+			}
+			assert_eq!(stella.decompose(&Integer::from(&output.n + 1), &job), None);
+		}
+	}
+	
+	#[test]
+	fn job_builder_defaults_and_validation() {
+		let job = Job::builder(1, SEPTUPLET.to_vec()).target_min(Integer::from(100)).target_max(Integer::from(200)).build().unwrap();
+		assert_eq!(job.k_min, SEPTUPLET.len());
+		assert_eq!(job.pattern_min, vec![true; SEPTUPLET.len()]);
+		assert!(!job.clear_previous_jobs && !job.dedup_outputs && !job.skip_primality_test);
+		assert_eq!((&job.alignment, job.factor_stride, job.partial_output_threshold), (&None, None, 0));
+		assert_eq!(job.min_output_spacing, 0);
+		assert!(job.validate().is_empty());
+		let error_count = |builder: JobBuilder| builder.build().err().map_or(0, |errors| errors.len());
+		let builder = || Job::builder(1, SEPTUPLET.to_vec()).target_min(Integer::from(100)).target_max(Integer::from(200));
+		assert_eq!(error_count(Job::builder(1, SEPTUPLET.to_vec()).target_min(Integer::from(100))), 1);
+		assert_eq!(error_count(builder().pattern_min(vec![true, true])), 1);
+		assert_eq!(error_count(builder().k_min(8)), 1);
+		assert_eq!(error_count(builder().target_max(Integer::from(99))), 1);
+		assert_eq!(error_count(builder().factor_stride(Some((3, 3)))), 1);
+		assert_eq!(error_count(builder().k_min(8).pattern_min(vec![true])), 2);
+		assert!(builder().target_max(Integer::from(100)).build().is_ok()); // Point search
+	}
+	
+	#[test]
+	fn tiny_memory_budget_forces_a_reduction() {
+		let params = Params {prime_table_limit: 1 << 20, ..test_params()};
+		let mut stella = Stella::new();
+		assert!(stella.set_params(params.clone()).is_empty());
+		let memory_usage = stella.estimated_memory_usage();
+		assert!(stella.set_params(Params {memory_budget: Some(memory_usage), ..params.clone()}).is_empty());
+		// The prime table is reduced first
+		assert_eq!(stella.set_params(Params {memory_budget: Some(memory_usage/4), ..params.clone()}).len(), 1);
+		assert!(stella.params.prime_table_limit < 1 << 20 && stella.params.prime_table_limit >= MIN_BUDGET_PRIME_TABLE_LIMIT);
+		assert_eq!(stella.params.workers, 4);
+		assert!(stella.estimated_memory_usage() <= memory_usage/4);
+		// Then the workers, and a warning is added if the budget still cannot be met
+		assert_eq!(stella.set_params(Params {memory_budget: Some(1), ..params}).len(), 2);
+		assert_eq!(stella.params.prime_table_limit, MIN_BUDGET_PRIME_TABLE_LIMIT);
+		assert_eq!(stella.params.workers, 1);
+		stella.init().unwrap();
+	}
+	
+	#[test]
+	fn too_small_or_too_big_prime_tables_give_an_init_error() {
+		let mut stella = Stella::new();
+		stella.set_params(Params {prime_table_limit: 1, ..test_params()});
+		assert_eq!(stella.init(), Err(InitError::PrimeTableTooSmall {primes: 0, needed: 12}));
+		stella.set_params(Params {prime_table_limit: 30, ..test_params()});
+		assert_eq!(stella.init(), Err(InitError::PrimeTableTooSmall {primes: 10, needed: 12}));
+		stella.set_params(Params {prime_table_limit: 0, prime_count: Some(1 << 30), ..test_params()});
+		assert_eq!(stella.init(), Err(InitError::PrimeCountTooBig {prime_count: 1 << 30}));
+		stella.set_params(Params {prime_table_limit: 37, ..test_params()});
+		assert_eq!(stella.init(), Ok(()));
+		assert!(StellaShared::new(Params {prime_table_limit: 1, ..test_params()}).is_err_and(|error| error == InitError::PrimeTableTooSmall {primes: 0, needed: 12}));
+	}
+	
+	#[test]
+	fn gaps_round_trip() {
+		assert_eq!(gaps_from_pattern(&SEPTUPLET), vec![2, 4, 2, 4, 6, 2]);
+		assert_eq!(pattern_from_gaps(&[2, 4, 2, 4, 6, 2]), Ok(SEPTUPLET.to_vec()));
+		for (_, pattern) in PATTERN_NAMES {
+			assert_eq!(pattern_from_gaps(&gaps_from_pattern(pattern)), Ok(pattern.to_vec()));
+		}
+		assert!(pattern_from_gaps(&[2, 2]).is_err()); // 0, 2, 4 is not admissible
+		assert!(pattern_from_gaps(&[2, 0]).is_err());
+		assert!(pattern_from_gaps(&[2, isize::MAX]).is_err());
+	}
+	
+	#[test]
+	fn hardy_littlewood_constants_match_the_published_ones() {
+		let primes = generate_primes(1 << 20);
+		assert!((hardy_littlewood_constant(&[0, 2], &primes) - 1.320324).abs() < 1e-5);
+		assert!((hardy_littlewood_constant(&[0, 2, 6], &primes) - 2.858249).abs() < 1e-5);
+		assert!((hardy_littlewood_constant(&[0, 4, 6], &primes) - 2.858249).abs() < 1e-5); // Same constant for the mirrored pattern
+		assert_eq!(hardy_littlewood_constant(&[0, 2, 4], &primes), 0f64); // Not admissible
+		assert_eq!(hardy_littlewood_constant(&[0, 2, 2, 0], &primes), hardy_littlewood_constant(&[0, 2], &primes)); // Repeated offsets are ignored
+	}
+	
+	#[test]
+	fn complementary_factor_strides_cover_the_whole_range() {
+		let (reference, reference_stats) = search_with_stats(test_params(), test_job(1));
+		let mut outputs = vec![];
+		let mut candidates_tested = 0;
+		for offset in 0 .. 3 {
+			let (stride_outputs, stats) = search_with_stats(test_params(), Job {factor_stride: Some((3, offset)), ..test_job(1)});
+			assert!(!stride_outputs.is_empty());
+			outputs.extend(stride_outputs);
+			candidates_tested += stats.candidates_tested;
+		}
+		outputs.sort();
+		assert_eq!(outputs, reference); // So no Output was found twice
+		assert_eq!(candidates_tested, reference_stats.candidates_tested);
+	}
+	
+	#[test]
+	fn euler_test_rejects_more_pseudoprimes_than_fermat() {
+		// Composite numbers below 10000 passing the tests in base 2 (OEIS A001567 and A047713)
+		let fermat_pseudoprimes = [341, 561, 645, 1105, 1387, 1729, 1905, 2047, 2465, 2701, 2821, 3277, 4033, 4369, 4371, 4681, 5461, 6601, 7957, 8321, 8481, 8911];
+		let euler_pseudoprimes = [561, 1105, 1729, 1905, 2047, 2465, 3277, 4033, 4681, 6601, 8321, 8481];
+		for test in [PrimalityTest::Fermat, PrimalityTest::Euler] {
+			let pseudoprimes: Vec<u64> = (0u64 .. 10000).filter(|&n| test.is_probable_prime(&Integer::from(n)) && Integer::from(n).is_probably_prime(30) == rug::integer::IsPrime::No).collect();
+			let primes_found = (0u64 .. 10000).filter(|&n| test.is_probable_prime(&Integer::from(n))).count() - pseudoprimes.len();
+			assert_eq!(primes_found, 1229); // No prime is rejected
+			match test {
+				PrimalityTest::Fermat => assert_eq!(pseudoprimes, fermat_pseudoprimes),
+				PrimalityTest::Euler => assert_eq!(pseudoprimes, euler_pseudoprimes)
+			}
+		}
+	}
+	
+	#[test]
+	fn max_factors_per_sieve_task_bounds_the_sieve_tasks() {
+		let (reference, reference_stats) = search_with_stats(test_params(), test_job(1));
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		stella.add_job(test_job(1));
+		let primorial_factor_max = stella.job_stats.lock().unwrap().get(&1).unwrap().primorial_factor_max;
+		assert_eq!(reference_stats.sieve_task_durations.count, primorial_factor_max.div_ceil(1 << 12));
+		for max_factors_per_sieve_task in [1000, 3000] {
+			let (outputs, stats) = search_with_stats(Params {max_factors_per_sieve_task: max_factors_per_sieve_task, ..test_params()}, test_job(1));
+			assert_eq!(stats.sieve_task_durations.count, primorial_factor_max.div_ceil(max_factors_per_sieve_task));
+			assert_eq!(stats.candidates_generated, reference_stats.candidates_generated);
+			assert_eq!(outputs, reference);
+		}
+	}
+	
+	#[test]
+	fn reused_sieve_buffers_have_no_stale_bits() {
+		// With small sieves shared by the workers, each buffer is reused for many windows, the last one ending in the middle of a word. A stale bit would eliminate a Candidate of a later window
+		let job = Job {target_max: (Integer::from(1) << 64) + (Integer::from(1) << 54) + 12345, ..test_job(1)};
+		let (reference, reference_stats) = search_with_stats(Params {sieve_size: 1 << 16, ..test_params()}, job.clone());
+		for (sieve_size, sieve_buffer_pool_size) in [(1 << 8, 0), (1 << 8, 1), (1 << 12, 2)] {
+			let (outputs, stats) = search_with_stats(Params {sieve_size: sieve_size, sieve_buffer_pool_size: sieve_buffer_pool_size, ..test_params()}, job.clone());
+			assert!(stats.sieve_task_durations.count > 1);
+			assert_eq!(stats.candidates_generated, reference_stats.candidates_generated);
+			assert_eq!(outputs, reference);
+		}
+	}
+	
+	#[test]
+	fn prime_count_estimate_is_within_one_percent() {
+		assert_eq!(prime_count_estimate(0), 0);
+		assert_eq!(prime_count_estimate(1), 0);
+		for limit in [100000, 1000000, 1 << 22] {
+			let prime_count = generate_primes(limit).len();
+			let error = (prime_count_estimate(limit) as f64 - prime_count as f64).abs()/(prime_count as f64);
+			assert!(error < 0.01, "{} {} {}", limit, prime_count, prime_count_estimate(limit));
+		}
+	}
+	
+	#[test]
+	fn single_sieve_window_completes_the_job_after_the_first_window() {
+		let pattern = vec![0, 2];
+		let mut stella = Stella::new();
+		stella.set_params(Params {
+			workers: 2,
+			constellation_pattern: pattern.clone(),
+			prime_table_limit: 2000,
+			primorial_number: 5,
+			sieve_size: 1024,
+			single_sieve_window: true,
+			..Default::default()
+		});
+		stella.init().unwrap();
+		stella.start_workers();
+		let primorial = stella.primorial();
+		let target_min = Integer::from(1000000000039u64);
+		let job = Job::builder(1, pattern.clone())
+			.target_min(target_min.clone())
+			.target_max(target_min.clone() + primorial.clone()*4321)
+			.skip_primality_test(true)
+			.build()
+			.unwrap();
+		let (handle, _) = stella.add_job_tracked(job).unwrap();
+		assert!(stella.wait_idle(Duration::from_secs(60)));
+		assert!(handle.is_complete());
+		assert_eq!(handle.progress(), 1f64);
+		// Exactly the Candidates of the first window (1024 primorial factors)
+		let base = target_min.clone() + &primorial - Integer::from(&target_min % &primorial) + stella.params.primorial_offset;
+		let sieving_primes: Vec<usize> = stella.primes.iter().filter(|&&p| !primorial.is_divisible_u(p as u32)).cloned().collect();
+		let mut expected_candidates = vec![];
+		for f in 0 .. 1024 {
+			let n = base.clone() + primorial.clone()*f;
+			if pattern.iter().all(|&offset| sieving_primes.iter().all(|&p| !Integer::from(&n + offset).is_divisible_u(p as u32))) {
+				expected_candidates.push(n);
+			}
+		}
+		let mut candidates: Vec<Integer> = handle.outputs().into_iter().map(|output| output.n).collect();
+		candidates.sort();
+		assert!(!candidates.is_empty());
+		assert_eq!(candidates, expected_candidates);
+		stella.stop_workers();
+	}
+	
+	#[test]
+	fn find_best_keeps_the_stats_threads_and_running_workers() {
+		let reference = search(test_params(), test_job(1));
+		let longest = reference.iter().map(|output| output.pattern.len()).max().unwrap();
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		let snapshots = Arc::new(AtomicUsize::new(0));
+		let thread_snapshots = snapshots.clone();
+		stella.on_stats_interval(Duration::from_millis(10), Arc::new(move |_| {thread_snapshots.fetch_add(1, Ordering::SeqCst);})).unwrap();
+		let best = stella.find_best(test_job(1), Duration::from_secs(60)).unwrap();
+		assert_eq!(best.pattern.len(), longest);
+		assert!(stella.worker_threads.is_empty());
+		assert_eq!(stella.stats_threads.len(), 1);
+		let snapshots_after_find_best = snapshots.load(Ordering::SeqCst);
+		thread::sleep(Duration::from_millis(50));
+		assert!(snapshots.load(Ordering::SeqCst) > snapshots_after_find_best);
+		// With running workers, nothing is done
+		stella.start_workers();
+		assert!(stella.find_best(test_job(2), Duration::from_secs(60)).is_none());
+		assert_eq!(stella.worker_threads.len(), test_params().workers);
+		assert!(!stella.jobs.lock().unwrap().contains_key(&2));
+		stella.stop_workers();
+		assert!(stella.stats_threads.is_empty());
+	}
+	
+	#[test]
+	fn calibrate_keeps_the_job_with_the_same_id() {
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		let (_, errors) = stella.add_job(test_job(1)); // Queued while the workers are stopped
+		assert!(errors.is_empty());
+		assert_eq!(stella.calibrate(&test_job(1), 200).len(), SEPTUPLET.len());
+		assert!(stella.jobs.lock().unwrap().contains_key(&1));
+		assert_eq!(stella.jobs.lock().unwrap().len(), 1);
+		assert_eq!(stella.job_stats.lock().unwrap().len(), 1);
+		assert!(stella.output.lock().unwrap().iter().all(|output| output.job_id == 1)); // The workers also processed the Job meanwhile
+		// With running workers, nothing is done
+		stella.start_workers();
+		assert!(stella.calibrate(&test_job(2), 200).is_empty());
+		assert_eq!(stella.worker_threads.len(), test_params().workers);
+		assert!(stella.wait_idle(Duration::from_secs(60)));
+		stella.stop_workers();
+		assert_eq!(stella.drain_outputs_sorted(), search(test_params(), test_job(1)));
+	}
+	
+	#[test]
+	fn invalid_prime_counts_keep_the_previous_prime_table() {
+		let mut stella = Stella::new();
+		assert_eq!(stella.set_params(Params {prime_count: Some(0), ..test_params()}).len(), 1);
+		assert_eq!((stella.params.prime_table_limit, stella.params.prime_count), (16777216, None));
+		assert!(stella.set_params(Params {prime_table_limit: 0, prime_count: Some(1000), ..test_params()}).is_empty());
+		assert_eq!((stella.params.prime_table_limit, stella.params.prime_count), (0, Some(1000)));
+		assert_eq!(stella.set_params(Params {prime_count: Some(2000), ..test_params()}).len(), 1); // The limit is also set
+		assert_eq!((stella.params.prime_table_limit, stella.params.prime_count), (0, Some(1000)));
+		stella.init().unwrap();
+		assert_eq!(stella.primes.len(), 1000);
+	}
+	
+	#[test]
+	fn too_small_sieve_sizes_keep_the_previous_value() {
+		let mut stella = Stella::new();
+		assert_eq!(stella.set_params(Params {sieve_size: WORD_SIZE - 1, ..test_params()}).len(), 1);
+		assert_eq!(stella.params.sieve_size, 1 << 25);
+		assert!(stella.set_params(test_params()).is_empty());
+		assert_eq!(stella.set_params(Params {sieve_size: 1, ..test_params()}).len(), 1);
+		assert_eq!(stella.params.sieve_size, test_params().sieve_size);
+	}
+	
+	#[test]
+	fn invalid_dedicated_workers_keep_the_previous_values() {
+		let mut stella = Stella::new();
+		assert!(stella.set_params(Params {sieve_workers: 1, check_workers: 2, ..test_params()}).is_empty());
+		assert_eq!(stella.set_params(Params {sieve_workers: 3, check_workers: 2, ..test_params()}).len(), 1);
+		assert_eq!((stella.params.sieve_workers, stella.params.check_workers), (1, 2));
+		assert_eq!(stella.set_params(Params {sieve_workers: 4, ..test_params()}).len(), 1);
+		assert_eq!((stella.params.sieve_workers, stella.params.check_workers), (1, 2));
+		// The previous values are dropped if they do not fit the new number of workers either
+		assert_eq!(stella.set_params(Params {workers: 2, sieve_workers: 2, ..test_params()}).len(), 1);
+		assert_eq!((stella.params.sieve_workers, stella.params.check_workers), (0, 0));
+	}
+	
+	#[test]
+	fn zero_heartbeat_intervals_keep_the_previous_value() {
+		let mut stella = Stella::new();
+		assert_eq!(stella.set_params(Params {heartbeat_interval: Some(Duration::ZERO), ..test_params()}).len(), 1);
+		assert_eq!(stella.params.heartbeat_interval, None);
+		assert!(stella.set_params(Params {heartbeat_interval: Some(Duration::from_secs(1)), ..test_params()}).is_empty());
+		assert_eq!(stella.set_params(Params {heartbeat_interval: Some(Duration::ZERO), ..test_params()}).len(), 1);
+		assert_eq!(stella.params.heartbeat_interval, Some(Duration::from_secs(1)));
+	}
+	
+	#[test]
+	fn zero_stats_intervals_give_an_error() {
+		let mut stella = Stella::new();
+		assert!(stella.on_stats_interval(Duration::ZERO, Arc::new(|_| {})).is_err());
+		assert!(stella.stats_threads.is_empty());
+	}
+	
+	#[test]
+	fn empty_bloom_filters_get_one_bit_and_one_hash() {
+		let mut filter = BloomFilter::new(0, 0);
+		assert_eq!(filter, BloomFilter::new(1, 1));
+		assert!(!filter.contains(&Integer::from(7)));
+		assert!(filter.insert(&Integer::from(7)));
+		assert!(filter.contains(&Integer::from(7)));
+		assert_eq!(BloomFilter::from_bytes(&filter.to_bytes()), Some(filter));
+	}
+	
+	#[test]
+	fn self_test_passes() {
+		assert_eq!(Stella::self_test(), Ok(()));
+	}
+	
+	#[test]
+	fn job_handles_see_the_job_complete() {
+		let reference = search(test_params(), test_job(1));
+		let mut stella = Stella::new();
+		stella.set_params(test_params());
+		stella.init().unwrap();
+		stella.start_workers();
+		let (handle, _) = stella.add_job_tracked(test_job(1)).unwrap();
+		assert_eq!(handle.job_id(), 1);
+		assert!(stella.wait_idle(Duration::from_secs(60)));
+		assert!(handle.is_complete());
+		assert_eq!(handle.progress(), 1f64);
+		let mut outputs = handle.outputs();
+		outputs.sort_by(|a, b| a.n.cmp(&b.n));
+		assert_eq!(outputs, reference);
+		assert!(handle.outputs().is_empty());
+		stella.stop_workers();
+	}
+	
+	#[test]
+	fn canceled_job_handles_remove_the_queued_tasks() {
+		let mut stella = Stella::new();
+		stella.set_params(Params {max_factors_per_sieve_task: 256, ..test_params()});
+		stella.init().unwrap();
+		stella.start_workers();
+		let (handle, _) = stella.add_job_tracked(Job {target_max: (Integer::from(1) << 64) + (Integer::from(1) << 62), ..test_job(1)}).unwrap();
+		let (_, errors) = stella.add_job(test_job(2));
+		assert!(errors.is_empty());
+		while handle.progress() == 0f64 {
+			thread::sleep(Duration::from_millis(1));
+		}
+		stella.stop_workers(); // The queued Tasks are kept
+		assert!(handle.progress() < 1f64);
+		assert!(stella.tasks.lock().unwrap().iter().any(|task| task.job_id == 1));
+		handle.cancel();
+		assert!(stella.tasks.lock().unwrap().iter().all(|task| task.job_id != 1));
+		assert!(!stella.jobs.lock().unwrap().contains_key(&1));
+		let progress = handle.progress();
+		stella.start_workers();
+		assert!(stella.wait_idle(Duration::from_secs(60)));
+		stella.stop_workers();
+		assert!(!handle.is_complete());
+		assert_eq!(handle.progress(), progress);
+		assert_eq!(stella.job_stats(2).unwrap().progress(), 1f64); // The other Job is not affected
+	}
+	
+	#[test]
+	fn heartbeats_are_emitted_at_the_interval_until_the_workers_stop() {
+		let mut stella = Stella::new();
+		stella.set_params(Params {heartbeat_interval: Some(Duration::from_millis(50)), ..test_params()});
+		stella.init().unwrap();
+		stella.start_workers();
+		thread::sleep(Duration::from_millis(275)); // No Job, so the heartbeats come even if nothing is found
+		stella.stop_workers();
+		let mut heartbeats = 0;
+		while let Some(Event::Heartbeat(_)) = stella.pop_event() {
+			heartbeats += 1;
+		}
+		assert!((3 ..= 7).contains(&heartbeats), "{} heartbeats", heartbeats); // 5 expected, with some slack for the scheduling
+		thread::sleep(Duration::from_millis(150));
+		assert!(stella.pop_event().is_none());
+	}
 }
@@ -0,0 +1,251 @@
+// (c) 2023 Pttn (Stelo.xyz/Riecoin.dev) and contributors
+// Helpers to use Stella as the compute core of a Riecoin miner
+
+use rug::Integer;
+use rug::ops::RemRounding;
+use crate::{Job, Output};
+
+// Number of bits before the free part of a Riecoin target: a leading 1, 8 zeros, and the 256 bits of the header hash
+pub const TARGET_PREFIX_BITS: u32 = 265;
+
+// Relevant data of a mining work received from a Riecoin node or pool (for example via Stratum's mining.notify)
+// The header hash must be computed by the caller from the protocol specific block header (previous block hash, Merkle Root built with the coinbase parts, time, bits,...)
+#[derive(Clone)]
+pub struct WorkTemplate {
+	pub header_hash: [u8; 32], // Interpreted as a big endian number
+	pub difficulty: u32, // Bit length of the target
+	pub pattern: Vec<isize>, // Constellation Pattern accepted by the network
+	pub k_min: usize // Tuple length to output, lower than the pattern length for pool shares
+}
+
+// Computes the target of a work: 1, 8 zeros, the header hash, then zeros up to the difficulty length. The base prime of a valid tuple must be the target plus an offset lower than 2^(difficulty - 265).
+pub fn target_from_work(work: &WorkTemplate) -> Option<Integer> {
+	if work.difficulty < TARGET_PREFIX_BITS {return None;}
+	let mut target = Integer::from(1) << 264;
+	target += Integer::from_digits(&work.header_hash, rug::integer::Order::Msf);
+	return Some(target << (work.difficulty - TARGET_PREFIX_BITS));
+}
+
+// Number of about difficulty bits, 2^difficulty rounded down (with the precision of a f64 for the fractional part), for the protocols giving fractional difficulties
+// None if the difficulty is not finite or lower than 1
+pub fn difficulty_to_target(difficulty: f64) -> Option<Integer> {
+	if !difficulty.is_finite() || difficulty < 1f64 {return None;}
+	let exponent = difficulty.floor() as u32;
+	let mantissa = Integer::from_f64((difficulty - difficulty.floor()).exp2()*((1u64 << 52) as f64)).unwrap(); // 2^fraction in [1, 2), with 52 bits of precision
+	return Some(match exponent >= 52 {
+		true => mantissa << (exponent - 52),
+		false => mantissa >> (52 - exponent)
+	});
+}
+
+// For pool shares (k_min lower than the pattern length), only the first two numbers are required to be prime
+fn pool_pattern_min(pattern_length: usize, k_min: usize) -> Vec<bool> {
+	return (0 .. pattern_length).map(|i| k_min == pattern_length || i < 2).collect();
+}
+
+// Makes a Job searching the whole valid range of a work, replacing the previous Jobs since the previous works are obsolete
+pub fn job_from_work(work: &WorkTemplate, job_id: usize) -> Result<Job, String> {
+	let target_min = match target_from_work(work) {
+		Some(target_min) => target_min,
+		None => {return Err(format!("The difficulty {} is too low, it must be at least {}.", work.difficulty, TARGET_PREFIX_BITS));}
+	};
+	let target_max = target_min.clone() + (Integer::from(1) << (work.difficulty - TARGET_PREFIX_BITS)) - 1;
+	return Ok(Job {
+		id: job_id,
+		clear_previous_jobs: true,
+		pattern: work.pattern.clone(),
+		target_min: target_min,
+		target_max: target_max,
+		k_min: work.k_min,
+		pattern_min: pool_pattern_min(work.pattern.len(), work.k_min),
+		dedup_outputs: true, // Never submit twice the same share or block
+		alignment: None,
+		skip_primality_test: false,
+		partial_output_threshold: 0,
+		min_output_spacing: Integer::from(0),
+		factor_stride: None
+	});
+}
+
+impl Job {
+	// Same as job_from_work, but the base prime must also end with the given bits, n ≡ trailing_value (mod 2^trailing_bits), for the encodings putting data in the low bits of the offset
+	// This is done with an alignment of 2^trailing_bits, so the Candidates are spaced by the primorial times 2^(trailing_bits - 1), and add_job rejects the Job if this exceeds the range
+	// The target_min of the Job includes the trailing value, so the shares must be encoded relatively to target_from_work
+	pub fn riecoin(job_id: usize, work: &WorkTemplate, trailing_bits: u32, trailing_value: &Integer) -> Result<Job, String> {
+		let mut job = job_from_work(work, job_id)?;
+		if trailing_bits == 0 {
+			return Ok(job);
+		}
+		if trailing_bits >= work.difficulty - TARGET_PREFIX_BITS {
+			return Err(format!("The {} trailing bits do not fit in the {} bits of the offset.", trailing_bits, work.difficulty - TARGET_PREFIX_BITS));
+		}
+		let modulus = Integer::from(1) << trailing_bits;
+		if *trailing_value < 0 || *trailing_value >= modulus {
+			return Err(format!("The trailing value {} does not fit in {} bits.", trailing_value, trailing_bits));
+		}
+		if trailing_value.is_even() {
+			return Err("The trailing value must be odd, as the base prime is odd.".to_string());
+		}
+		job.target_min += trailing_value; // The low bits of the target are zeros
+		job.alignment = Some(modulus);
+		return Ok(job);
+	}
+	
+	// Job for a miner not using the WorkTemplate: searches the 2^range_bits numbers from difficulty_to_target(difficulty), only the ones satisfying n ≡ residue (mod modulus) with header_congruence = (modulus, residue)
+	// k_min is the tuple length to output, the first two numbers being the only required ones if it is lower than the pattern length (pool shares)
+	// The congruence is applied with an alignment, so add_job also checks that it is compatible with the primorial and that the Candidate stride does not exceed the range
+	pub fn for_mining(id: usize, pattern: Vec<isize>, k_min: usize, difficulty: f64, header_congruence: (Integer, Integer), range_bits: u32) -> Result<Job, String> {
+		let target = match difficulty_to_target(difficulty) {
+			Some(target) => target,
+			None => {return Err(format!("Invalid difficulty {}.", difficulty));}
+		};
+		let (modulus, residue) = header_congruence;
+		if modulus <= 0 {
+			return Err("The congruence modulus must be positive.".to_string());
+		}
+		if residue < 0 || residue >= modulus {
+			return Err(format!("The congruence residue {} must be lower than the modulus {}.", residue, modulus));
+		}
+		if modulus > 1 && Integer::from(residue.gcd_ref(&modulus)) != 1 {
+			return Err(format!("The congruence residue {} must be coprime to the modulus {}, otherwise all the numbers would share a factor with it.", residue, modulus));
+		}
+		let range = Integer::from(1) << range_bits;
+		if modulus > range {
+			return Err(format!("The congruence modulus {} exceeds the range of 2^{} numbers.", modulus, range_bits));
+		}
+		let target_max = target.clone() + range;
+		let target_min = target.clone() + Integer::from(&residue - &target).rem_euc(&modulus); // First number at or above the target satisfying the congruence
+		let job = Job {
+			id: id,
+			clear_previous_jobs: true,
+			pattern_min: pool_pattern_min(pattern.len(), k_min),
+			pattern: pattern,
+			target_min: target_min,
+			target_max: target_max,
+			k_min: k_min,
+			dedup_outputs: true,
+			alignment: if modulus > 1 {Some(modulus)} else {None},
+			skip_primality_test: false,
+			partial_output_threshold: 0,
+			min_output_spacing: Integer::from(0),
+			factor_stride: None
+		};
+		let errors = job.validate();
+		if !errors.is_empty() {
+			return Err(errors.join(" "));
+		}
+		return Ok(job);
+	}
+}
+
+impl Output {
+	// Offset n - target_min to submit to a pool, as big endian bytes without leading zeros (empty for a zero offset). None if n is below target_min.
+	pub fn share_encoding(&self, target_min: &Integer) -> Option<Vec<u8>> {
+		let offset = Integer::from(&self.n - target_min);
+		if offset < 0 {return None;}
+		return Some(offset.to_digits::<u8>(rug::integer::Order::Msf));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	const SEPTUPLET: [isize; 7] = [0, 2, 6, 8, 12, 18, 20];
+	
+	// Work whose header hash is the number 1
+	fn test_work(difficulty: u32, k_min: usize) -> WorkTemplate {
+		let mut header_hash = [0u8; 32];
+		header_hash[31] = 1;
+		return WorkTemplate {header_hash: header_hash, difficulty: difficulty, pattern: SEPTUPLET.to_vec(), k_min: k_min};
+	}
+	
+	#[test]
+	fn target_from_work_puts_the_header_hash_after_the_prefix() {
+		assert_eq!(target_from_work(&test_work(265, 7)), Some((Integer::from(1) << 264) + 1));
+		assert_eq!(target_from_work(&test_work(300, 7)), Some(((Integer::from(1) << 264) + 1) << 35));
+		assert_eq!(target_from_work(&test_work(264, 7)), None);
+		let mut work = test_work(265, 7);
+		work.header_hash = [0xff; 32];
+		assert_eq!(target_from_work(&work), Some((Integer::from(1) << 264) + (Integer::from(1) << 256) - 1));
+	}
+	
+	#[test]
+	fn job_from_work_covers_the_valid_offsets() {
+		let job = job_from_work(&test_work(300, 7), 3).unwrap();
+		let target = target_from_work(&test_work(300, 7)).unwrap();
+		assert_eq!(job.id, 3);
+		assert_eq!(job.target_min, target);
+		assert_eq!(job.target_max, target + (Integer::from(1) << 35) - 1);
+		assert_eq!(job.pattern_min, vec![true; 7]);
+		assert!(job.clear_previous_jobs && job.dedup_outputs);
+		assert!(job.validate().is_empty());
+		// Pool shares only require the first two numbers
+		let share_job = job_from_work(&test_work(300, 4), 3).unwrap();
+		assert_eq!(share_job.pattern_min, vec![true, true, false, false, false, false, false]);
+		assert!(job_from_work(&test_work(264, 7), 3).is_err());
+	}
+	
+	#[test]
+	fn share_encodings_decode_back_to_the_base_prime() {
+		let target = target_from_work(&test_work(300, 4)).unwrap();
+		let output = |n: Integer| Output {n: n, pattern: SEPTUPLET[0 .. 4].to_vec(), job_id: 1, worker_id: 0, verified: true, seq: 0};
+		let n = target.clone() + 0x0102_0304u64;
+		let encoding = output(n.clone()).share_encoding(&target).unwrap();
+		assert_eq!(encoding, vec![1, 2, 3, 4]);
+		assert_eq!(target.clone() + Integer::from_digits(&encoding, rug::integer::Order::Msf), n);
+		assert_eq!(output(target.clone()).share_encoding(&target), Some(vec![]));
+		assert_eq!(output(target.clone() - 1).share_encoding(&target), None);
+	}
+	
+	#[test]
+	fn riecoin_jobs_satisfy_the_trailing_bits() {
+		let work = test_work(300, 7);
+		let target = target_from_work(&work).unwrap();
+		assert!(Job::riecoin(1, &work, 0, &Integer::from(0)).unwrap().alignment.is_none());
+		let job = Job::riecoin(1, &work, 8, &Integer::from(0x5b)).unwrap();
+		assert_eq!(job.target_min, target.clone() + 0x5b);
+		assert_eq!(job.alignment, Some(Integer::from(256)));
+		// The Candidates end with the trailing bits and stay in the valid range of the work
+		let mut stella = crate::Stella::new();
+		stella.set_params(crate::Params {workers: 1, constellation_pattern: SEPTUPLET.to_vec(), prime_table_limit: 1 << 12, primorial_number: 5, exact_range_start: true, ..Default::default()});
+		stella.init().unwrap();
+		let (first_candidate, last_candidate) = stella.candidate_range(&job);
+		for candidate in [&first_candidate, &last_candidate] {
+			assert_eq!(Integer::from(candidate % 256), 0x5b);
+			assert!(*candidate >= target && *candidate < target.clone() + (Integer::from(1) << 35));
+		}
+		assert!(Job::riecoin(1, &work, 8, &Integer::from(0x5a)).is_err()); // Even
+		assert!(Job::riecoin(1, &work, 8, &Integer::from(257)).is_err()); // Too big
+		assert!(Job::riecoin(1, &work, 35, &Integer::from(1)).is_err()); // Does not fit in the offset
+	}
+	
+	#[test]
+	fn difficulty_to_target_rounds_down_the_power_of_two() {
+		assert_eq!(difficulty_to_target(1f64), Some(Integer::from(2)));
+		assert_eq!(difficulty_to_target(10f64), Some(Integer::from(1024)));
+		assert_eq!(difficulty_to_target(10.5), Some(Integer::from(1448))); // 2^10.5 = 1448.15...
+		assert_eq!(difficulty_to_target(100f64), Some(Integer::from(1) << 100));
+		let target = difficulty_to_target(100.25).unwrap(); // 2^0.25 = 1.18920...
+		assert!(target > (Integer::from(11892) << 100)/10000 && target < (Integer::from(11893) << 100)/10000);
+		for difficulty in [0.5, -1f64, f64::NAN, f64::INFINITY] {
+			assert_eq!(difficulty_to_target(difficulty), None);
+		}
+	}
+	
+	#[test]
+	fn mining_jobs_satisfy_the_header_congruence() {
+		let target = Integer::from(1) << 100u32;
+		let job = Job::for_mining(2, SEPTUPLET.to_vec(), 4, 100f64, (Integer::from(1000), Integer::from(7)), 20).unwrap();
+		assert_eq!(job.target_min, target.clone() + 631); // 2^100 ≡ 376 (mod 1000), so the first number ≡ 7 is 2^100 + 7 - 376 + 1000
+		assert_eq!(job.target_max, target.clone() + (1 << 20));
+		assert_eq!(job.alignment, Some(Integer::from(1000)));
+		assert_eq!(job.pattern_min, vec![true, true, false, false, false, false, false]);
+		let unconstrained_job = Job::for_mining(2, SEPTUPLET.to_vec(), 7, 100f64, (Integer::from(1), Integer::from(0)), 20).unwrap();
+		assert_eq!((unconstrained_job.target_min, unconstrained_job.alignment), (target, None));
+		for (modulus, residue) in [(0u32, 0u32), (1000, 1000), (1000, 5), (1 << 21, 1)] { // Zero modulus, too big residue, residue not coprime, modulus exceeding the range
+			assert!(Job::for_mining(2, SEPTUPLET.to_vec(), 4, 100f64, (Integer::from(modulus), Integer::from(residue)), 20).is_err());
+		}
+		assert!(Job::for_mining(2, SEPTUPLET.to_vec(), 4, 0.5, (Integer::from(1), Integer::from(0)), 20).is_err());
+	}
+}